@@ -0,0 +1,684 @@
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use clap::{Args, Parser, Subcommand};
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use serde::Serialize;
+
+use crate::client::ApiClient;
+use crate::config::Config;
+use crate::contract_evaluator::ContractEvaluator;
+use crate::error::{Result, SpaceTradersError};
+use crate::fleet::{FleetContext, FleetController};
+use crate::market_store::MarketStore;
+use crate::models::{
+    AgentData, Cargo, Contract, Market, Ship, ShipFuel, ShipNav, ShipNavStatus, ShipType,
+    TradeSymbol, WaypointTraitSymbol, WaypointType,
+};
+use crate::navigation::Navigator;
+use crate::shipyard::PurchasePlanner;
+use crate::survey_cache::SurveyCache;
+use crate::waypoints::{system_symbol_of, WaypointQuery};
+
+mod table;
+
+pub use table::Table;
+
+/// Command-line interface to the bot and the API.
+#[derive(Parser, Debug)]
+#[command(name = "SpaceTraders", version, about)]
+pub struct Cli {
+    /// Print results as JSON instead of tables.
+    #[arg(long, global = true)]
+    pub json: bool,
+    /// Config file; `spacetraders.toml` is read if it exists.
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Register a new agent and save its token to `.env`.
+    Register {
+        /// Agent symbol; `agent.symbol` from the config, or a random one
+        /// with `agent.symbol_prefix`, if not given.
+        #[arg(long)]
+        symbol: Option<String>,
+        /// `agent.faction` from the config if not given.
+        #[arg(long)]
+        faction: Option<String>,
+    },
+    /// Show the agent's credits and headquarters.
+    Agent,
+    #[command(subcommand)]
+    Contracts(ContractsCommand),
+    #[command(subcommand)]
+    Ships(ShipsCommand),
+    #[command(subcommand)]
+    Ship(ShipCommand),
+    #[command(subcommand)]
+    Waypoints(WaypointsCommand),
+    #[command(subcommand)]
+    Shipyard(ShipyardCommand),
+    #[command(subcommand)]
+    Market(MarketCommand),
+    /// Run the fleet automation until interrupted.
+    Run,
+}
+
+/// The agent's contracts.
+#[derive(Subcommand, Debug)]
+pub enum ContractsCommand {
+    List,
+    Accept {
+        id: String,
+    },
+    /// Deliver goods from a ship docked at the contract's destination.
+    Deliver {
+        id: String,
+        ship: String,
+        good: String,
+        units: u32,
+    },
+    Fulfill {
+        id: String,
+    },
+}
+
+/// The agent's fleet.
+#[derive(Subcommand, Debug)]
+pub enum ShipsCommand {
+    List,
+    Show { ship: String },
+}
+
+/// Single actions for one ship.
+#[derive(Subcommand, Debug)]
+pub enum ShipCommand {
+    Orbit {
+        ship: String,
+    },
+    Dock {
+        ship: String,
+    },
+    Navigate {
+        ship: String,
+        waypoint: String,
+    },
+    Refuel {
+        ship: String,
+    },
+    Extract {
+        ship: String,
+    },
+    /// Sell cargo at the market the ship is docked at.
+    Sell {
+        ship: String,
+        good: String,
+        /// Everything of that good in the hold if not given.
+        units: Option<u32>,
+    },
+}
+
+/// Waypoint search.
+#[derive(Subcommand, Debug)]
+pub enum WaypointsCommand {
+    Find(FindWaypoints),
+}
+
+#[derive(Args, Debug)]
+pub struct FindWaypoints {
+    system: String,
+    /// Only waypoints of this type, e.g. ENGINEERED_ASTEROID.
+    #[arg(long = "type")]
+    waypoint_type: Option<String>,
+    /// Only waypoints with this trait, e.g. MARKETPLACE; repeatable.
+    #[arg(long = "trait")]
+    traits: Vec<String>,
+}
+
+/// Ships for sale.
+#[derive(Subcommand, Debug)]
+pub enum ShipyardCommand {
+    /// Ships offered at a shipyard; prices need a ship present.
+    List {
+        waypoint: String,
+    },
+    Buy {
+        waypoint: String,
+        ship_type: String,
+    },
+}
+
+/// Market prices.
+#[derive(Subcommand, Debug)]
+pub enum MarketCommand {
+    /// Goods traded at a market; prices need a ship present.
+    Show { waypoint: String },
+}
+
+/// Symbols are accepted in any case on the command line.
+fn symbol<T: for<'a> From<&'a str>>(value: &str) -> T {
+    T::from(value.to_uppercase().as_str())
+}
+
+fn time(time: &DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn contract_status(contract: &Contract) -> &'static str {
+    if contract.fulfilled {
+        "fulfilled"
+    } else if contract.accepted {
+        "accepted"
+    } else {
+        "offered"
+    }
+}
+
+fn contracts_table(contracts: &[Contract]) -> Table {
+    let mut table = Table::new(&[
+        "ID",
+        "TYPE",
+        "STATUS",
+        "DELIVER",
+        "ON ACCEPT",
+        "ON FULFILL",
+        "DEADLINE",
+    ]);
+    for contract in contracts {
+        let deliveries: Vec<String> = contract
+            .terms
+            .deliveries
+            .iter()
+            .map(|delivery| {
+                format!(
+                    "{}/{} {} -> {}",
+                    delivery.units_fulfilled,
+                    delivery.units_required,
+                    delivery.trade_symbol,
+                    delivery.destination_symbol
+                )
+            })
+            .collect();
+        table.push(vec![
+            contract.id.clone(),
+            contract.contract_type.to_string(),
+            contract_status(contract).to_string(),
+            deliveries.join(", "),
+            contract.terms.payment.payment_on_accepted.to_string(),
+            contract.terms.payment.payment_on_fulfilled.to_string(),
+            time(&contract.terms.deadline),
+        ]);
+    }
+    table
+}
+
+fn agent_table(agent: &AgentData) -> Table {
+    Table::fields(vec![
+        ("Symbol", agent.symbol.clone()),
+        ("Faction", agent.starting_faction.clone()),
+        ("Headquarters", agent.headquarters.clone()),
+        ("Credits", agent.credits.to_string()),
+        ("Ships", agent.ship_count.to_string()),
+    ])
+}
+
+fn cargo_table(cargo: &Cargo) -> Table {
+    let mut table = Table::new(&["GOOD", "UNITS"]);
+    for item in &cargo.inventory {
+        table.push(vec![item.symbol.to_string(), item.units.to_string()]);
+    }
+    table
+}
+
+fn fuel(fuel: &ShipFuel) -> String {
+    format!("{}/{}", fuel.current, fuel.capacity)
+}
+
+fn nav_fields(nav: &ShipNav) -> Vec<(&'static str, String)> {
+    let mut fields = vec![
+        ("Status", nav.status.to_string()),
+        ("Location", nav.waypoint_symbol.clone()),
+        ("Flight mode", nav.flight_mode.to_string()),
+    ];
+    if nav.status == ShipNavStatus::InTransit {
+        fields.push((
+            "Route",
+            format!(
+                "{} -> {}",
+                nav.route.origin.symbol, nav.route.destination.symbol
+            ),
+        ));
+        fields.push(("Arrival", time(&nav.route.arrival)));
+    }
+    fields
+}
+
+fn ships_table(ships: &[Ship]) -> Table {
+    let mut table = Table::new(&[
+        "SYMBOL", "ROLE", "STATUS", "LOCATION", "MODE", "FUEL", "CARGO",
+    ]);
+    for ship in ships {
+        table.push(vec![
+            ship.symbol.clone(),
+            ship.registration.role.to_string(),
+            ship.nav.status.to_string(),
+            ship.nav.waypoint_symbol.clone(),
+            ship.nav.flight_mode.to_string(),
+            fuel(&ship.fuel),
+            format!("{}/{}", ship.cargo.units, ship.cargo.capacity),
+        ]);
+    }
+    table
+}
+
+fn ship_details(ship: &Ship) -> String {
+    let mut fields = vec![
+        ("Symbol", ship.symbol.clone()),
+        ("Role", ship.registration.role.to_string()),
+        ("Frame", ship.frame.name.clone().unwrap_or_default()),
+    ];
+    fields.extend(nav_fields(&ship.nav));
+    fields.push(("Fuel", fuel(&ship.fuel)));
+    fields.push((
+        "Cargo",
+        format!("{}/{}", ship.cargo.units, ship.cargo.capacity),
+    ));
+    if ship.cooldown.remaining_seconds > 0 {
+        fields.push(("Cooldown", format!("{}s", ship.cooldown.remaining_seconds)));
+    }
+    let mut details = Table::fields(fields).to_string();
+    if !ship.cargo.inventory.is_empty() {
+        details.push('\n');
+        details.push_str(&cargo_table(&ship.cargo).to_string());
+    }
+    details
+}
+
+fn market_table(market: &Market) -> Table {
+    if market.trade_goods.is_empty() {
+        let mut table = Table::new(&["GOOD", "TYPE"]);
+        for (goods, kind) in [
+            (&market.exports, "EXPORT"),
+            (&market.imports, "IMPORT"),
+            (&market.exchange, "EXCHANGE"),
+        ] {
+            for good in goods {
+                table.push(vec![good.symbol.to_string(), kind.to_string()]);
+            }
+        }
+        return table;
+    }
+    let mut table = Table::new(&["GOOD", "TYPE", "SUPPLY", "BUY", "SELL", "VOLUME"]);
+    for good in &market.trade_goods {
+        table.push(vec![
+            good.symbol.to_string(),
+            good.trade_type.to_string(),
+            good.supply.to_string(),
+            good.purchase_price.to_string(),
+            good.sell_price.to_string(),
+            good.trade_volume.to_string(),
+        ]);
+    }
+    table
+}
+
+impl Cli {
+    /// Prints `value` as JSON, or as the human-readable rendering.
+    fn print<T: Serialize, D: fmt::Display>(
+        &self,
+        value: &T,
+        human: impl FnOnce(&T) -> D,
+    ) -> Result<()> {
+        let mut stdout = io::stdout().lock();
+        if self.json {
+            writeln!(stdout, "{}", serde_json::to_string_pretty(value)?)?;
+        } else {
+            write!(stdout, "{}", human(value))?;
+        }
+        Ok(())
+    }
+
+    /// Whether the command can run without an agent token.
+    pub fn needs_token(&self) -> bool {
+        !matches!(self.command, Command::Register { .. })
+    }
+
+    pub async fn run(&self, client: ApiClient, config: &Config) -> Result<()> {
+        match &self.command {
+            Command::Register { symbol, faction } => {
+                let symbol = symbol
+                    .clone()
+                    .or_else(|| config.agent.symbol.clone())
+                    .unwrap_or_else(|| generate_random_symbol(&config.agent.symbol_prefix));
+                let faction = faction.as_ref().unwrap_or(&config.agent.faction);
+                let response = register(&client, &symbol, faction).await?;
+                self.print(&response, |response| {
+                    format!(
+                        "Registered {}; token saved to .env\n",
+                        response["data"]["agent"]["symbol"]
+                            .as_str()
+                            .unwrap_or(&symbol)
+                    )
+                })
+            }
+            Command::Agent => self.print(&client.get_agent_data().await?, agent_table),
+            Command::Contracts(command) => self.contracts(&client, command).await,
+            Command::Ships(ShipsCommand::List) => {
+                let ships = client.fetch_all::<Ship>("/my/ships").await?;
+                self.print(&ships, |ships| ships_table(ships))
+            }
+            Command::Ships(ShipsCommand::Show { ship }) => self.print(
+                &client.get_ship(&symbol::<String>(ship)).await?,
+                ship_details,
+            ),
+            Command::Ship(command) => self.ship(&client, command).await,
+            Command::Waypoints(WaypointsCommand::Find(find)) => {
+                let mut query = WaypointQuery::new(find.system.to_uppercase());
+                if let Some(waypoint_type) = &find.waypoint_type {
+                    query = query.waypoint_type(symbol::<WaypointType>(waypoint_type));
+                }
+                for name in &find.traits {
+                    query = query.with_trait(symbol::<WaypointTraitSymbol>(name));
+                }
+                let waypoints = client.find_waypoints(&query).await?;
+                self.print(&waypoints, |waypoints| {
+                    let mut table = Table::new(&["SYMBOL", "TYPE", "X", "Y", "TRAITS"]);
+                    for waypoint in waypoints {
+                        let traits: Vec<String> = waypoint
+                            .traits
+                            .iter()
+                            .map(|t| t.symbol.to_string())
+                            .collect();
+                        table.push(vec![
+                            waypoint.symbol.clone(),
+                            waypoint.waypoint_type.to_string(),
+                            waypoint.x.to_string(),
+                            waypoint.y.to_string(),
+                            traits.join(", "),
+                        ]);
+                    }
+                    table
+                })
+            }
+            Command::Shipyard(command) => self.shipyard(&client, command).await,
+            Command::Market(MarketCommand::Show { waypoint }) => {
+                let waypoint = waypoint.to_uppercase();
+                let market = client
+                    .get_market(system_symbol_of(&waypoint), &waypoint)
+                    .await?;
+                self.print(&market, market_table)
+            }
+            Command::Run => run_fleet(client, config).await,
+        }
+    }
+
+    async fn contracts(&self, client: &ApiClient, command: &ContractsCommand) -> Result<()> {
+        match command {
+            ContractsCommand::List => {
+                let contracts = client.get_contracts().await?;
+                self.print(&contracts, |contracts| contracts_table(contracts))
+            }
+            ContractsCommand::Accept { id } => {
+                let result = client.accept_contract(id).await?;
+                self.print(&result, |result| {
+                    format!(
+                        "{}\nCredits: {}\n",
+                        contracts_table(std::slice::from_ref(&result.contract)),
+                        result.agent.credits
+                    )
+                })
+            }
+            ContractsCommand::Deliver {
+                id,
+                ship,
+                good,
+                units,
+            } => {
+                let result = client
+                    .deliver_contract(id, &symbol::<String>(ship), &symbol(good), *units)
+                    .await?;
+                self.print(&result, |result| {
+                    format!(
+                        "{}\n{}",
+                        contracts_table(std::slice::from_ref(&result.contract)),
+                        cargo_table(&result.cargo)
+                    )
+                })
+            }
+            ContractsCommand::Fulfill { id } => {
+                let result = client.fulfill_contract(id).await?;
+                self.print(&result, |result| {
+                    format!(
+                        "{}\nCredits: {}\n",
+                        contracts_table(std::slice::from_ref(&result.contract)),
+                        result.agent.credits
+                    )
+                })
+            }
+        }
+    }
+
+    async fn ship(&self, client: &ApiClient, command: &ShipCommand) -> Result<()> {
+        match command {
+            ShipCommand::Orbit { ship } => {
+                let nav = client.send_ship_to_orbit(&symbol::<String>(ship)).await?;
+                self.print(&nav, |nav| Table::fields(nav_fields(nav)))
+            }
+            ShipCommand::Dock { ship } => {
+                let nav = client.dock_ship(&symbol::<String>(ship)).await?;
+                self.print(&nav, |nav| Table::fields(nav_fields(nav)))
+            }
+            ShipCommand::Navigate { ship, waypoint } => {
+                let result = client
+                    .navigate_to_waypoint(&symbol::<String>(ship), &waypoint.to_uppercase())
+                    .await?;
+                self.print(&result, |result| {
+                    let mut fields = nav_fields(&result.nav);
+                    fields.push(("Fuel", fuel(&result.fuel)));
+                    Table::fields(fields)
+                })
+            }
+            ShipCommand::Refuel { ship } => {
+                let result = client.refuel_ship(&symbol::<String>(ship)).await?;
+                self.print(&result, |result| {
+                    Table::fields(vec![
+                        ("Fuel", fuel(&result.fuel)),
+                        ("Paid", result.transaction.total_price.to_string()),
+                        ("Credits", result.agent.credits.to_string()),
+                    ])
+                })
+            }
+            ShipCommand::Extract { ship } => {
+                let result = client.extract_ores(&symbol::<String>(ship)).await?;
+                self.print(&result, |result| {
+                    let mined = &result.extraction.extraction_yield;
+                    Table::fields(vec![
+                        ("Extracted", format!("{} {}", mined.units, mined.symbol)),
+                        (
+                            "Cooldown",
+                            format!("{}s", result.cooldown.remaining_seconds),
+                        ),
+                        (
+                            "Cargo",
+                            format!("{}/{}", result.cargo.units, result.cargo.capacity),
+                        ),
+                    ])
+                })
+            }
+            ShipCommand::Sell { ship, good, units } => {
+                let ship = symbol::<String>(ship);
+                let good: TradeSymbol = symbol(good);
+                let units = match units {
+                    Some(units) => *units,
+                    None => {
+                        let held: u32 = client
+                            .get_ship_cargo(&ship)
+                            .await?
+                            .inventory
+                            .iter()
+                            .filter(|item| item.symbol == good)
+                            .map(|item| item.units)
+                            .sum();
+                        if held == 0 {
+                            return Err(SpaceTradersError::Command {
+                                message: format!("no {} in hold", good),
+                            });
+                        }
+                        held
+                    }
+                };
+                let result = client.sell_goods(&ship, &good, units).await?;
+                self.print(&result, |result| {
+                    let transaction = &result.transaction;
+                    Table::fields(vec![
+                        (
+                            "Sold",
+                            format!("{} {}", transaction.units, transaction.trade_symbol),
+                        ),
+                        ("Price", transaction.price_per_unit.to_string()),
+                        ("Total", transaction.total_price.to_string()),
+                        ("Credits", result.agent.credits.to_string()),
+                    ])
+                })
+            }
+        }
+    }
+
+    async fn shipyard(&self, client: &ApiClient, command: &ShipyardCommand) -> Result<()> {
+        match command {
+            ShipyardCommand::List { waypoint } => {
+                let waypoint = waypoint.to_uppercase();
+                let shipyard = client
+                    .find_ships_at_shipyard(system_symbol_of(&waypoint), &waypoint)
+                    .await?;
+                self.print(&shipyard, |shipyard| {
+                    let mut table = Table::new(&["TYPE", "NAME", "PRICE", "SUPPLY"]);
+                    if let Some(ships) = &shipyard.ships {
+                        for ship in ships {
+                            table.push(vec![
+                                ship.ship_type
+                                    .as_ref()
+                                    .map(ToString::to_string)
+                                    .unwrap_or_default(),
+                                ship.name.clone().unwrap_or_default(),
+                                ship.purchase_price
+                                    .map(|price| price.to_string())
+                                    .unwrap_or_default(),
+                                ship.supply
+                                    .as_ref()
+                                    .map(ToString::to_string)
+                                    .unwrap_or_default(),
+                            ]);
+                        }
+                    } else {
+                        for ship_type in shipyard.ship_types.iter().flatten() {
+                            let name = ship_type
+                                .ship_type
+                                .as_ref()
+                                .map(ToString::to_string)
+                                .unwrap_or_default();
+                            table.push(vec![name, String::new(), "-".to_string(), "-".to_string()]);
+                        }
+                    }
+                    table
+                })
+            }
+            ShipyardCommand::Buy {
+                waypoint,
+                ship_type,
+            } => {
+                let result = client
+                    .buy_ship(&waypoint.to_uppercase(), &symbol::<ShipType>(ship_type))
+                    .await?;
+                self.print(&result, |result| {
+                    Table::fields(vec![
+                        ("Ship", result.ship.symbol.clone()),
+                        ("Role", result.ship.registration.role.to_string()),
+                        (
+                            "Price",
+                            result.transaction.price.unwrap_or_default().to_string(),
+                        ),
+                        ("Credits", result.agent.credits.to_string()),
+                    ])
+                })
+            }
+        }
+    }
+}
+
+/// Registers `symbol` and keeps the response in `agent.json` and the token
+/// in `.env`, where the next run picks it up.
+async fn register(client: &ApiClient, symbol: &str, faction: &str) -> Result<serde_json::Value> {
+    let response = client.register_new_agent(symbol, faction).await?;
+    let token = response["data"]["token"].as_str().unwrap_or("").to_string();
+    let file = File::create("agent.json")?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer_pretty(&mut writer, &response)?;
+    writer.flush()?;
+    let mut env_file = OpenOptions::new().append(true).create(true).open(".env")?;
+
+    writeln!(env_file, "TOKEN={}", token)?;
+    Ok(response)
+}
+
+fn generate_random_symbol(prefix: &str) -> String {
+    let random_bytes: Vec<u8> = thread_rng().sample_iter(&Alphanumeric).take(6).collect();
+
+    format!("{}{}", prefix, STANDARD.encode(&random_bytes))
+}
+
+async fn run_fleet(client: ApiClient, config: &Config) -> Result<()> {
+    let home_system = match &config.fleet.home_system {
+        Some(system) => system.clone(),
+        None => {
+            let agent = client.get_agent_data().await?;
+            system_symbol_of(&agent.headquarters).to_string()
+        }
+    };
+    let fleet = client.fetch_all::<Ship>("/my/ships").await?;
+    for ship in &fleet {
+        println!(
+            "{} ({}) {} at {}",
+            ship.symbol, ship.registration.role, ship.nav.status, ship.nav.waypoint_symbol
+        );
+    }
+
+    let navigator = Navigator::new(
+        client.clone(),
+        Arc::new(client.route_planner(&home_system).await?),
+    );
+    let context = FleetContext {
+        navigator,
+        market_store: MarketStore::open(&config.fleet.market_store)?.shared(),
+        home_system,
+        mining: config.mining.mining_config(),
+        surveys: SurveyCache::new().shared(),
+        evaluator: ContractEvaluator::new(config.contracts.evaluator_config()),
+        credit_reserve: config.fleet.credit_reserve,
+    };
+
+    let agent_contracts = client.get_contracts().await?;
+    for contract in agent_contracts {
+        println!("Contract {}", contract);
+        context
+            .evaluator
+            .accept_if_profitable(&context, &fleet, &contract)
+            .await?;
+    }
+
+    FleetController::new(context, config.fleet.fleet_config())
+        .with_purchase_planner(PurchasePlanner::new(config.fleet.credit_reserve))
+        .run()
+        .await
+}
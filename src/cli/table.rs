@@ -0,0 +1,72 @@
+use std::fmt;
+
+/// Plain-text table with left-aligned columns, sized to their widest cell.
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(headers: &[&str]) -> Self {
+        Table {
+            headers: headers.iter().map(|header| header.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// A headerless two-column table of labelled values.
+    pub fn fields(fields: Vec<(&str, String)>) -> Self {
+        let mut table = Table::default();
+        for (label, value) in fields {
+            table.push(vec![format!("{}:", label), value]);
+        }
+        table
+    }
+
+    pub fn push(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    fn widths(&self) -> Vec<usize> {
+        let mut widths = vec![0; self.headers.len()];
+        for row in std::iter::once(&self.headers).chain(&self.rows) {
+            if widths.len() < row.len() {
+                widths.resize(row.len(), 0);
+            }
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        widths
+    }
+}
+
+fn write_row(f: &mut fmt::Formatter<'_>, row: &[String], widths: &[usize]) -> fmt::Result {
+    let line = row
+        .iter()
+        .zip(widths)
+        .map(|(cell, width)| format!("{:width$}", cell, width = width))
+        .collect::<Vec<_>>()
+        .join("  ");
+    writeln!(f, "{}", line.trim_end())
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let widths = self.widths();
+        if !self.headers.is_empty() {
+            write_row(f, &self.headers, &widths)?;
+            let rule: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+            write_row(f, &rule, &widths)?;
+        }
+        for row in &self.rows {
+            write_row(f, row, &widths)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,539 @@
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use reqwest::{
+    self,
+    header::{HeaderMap, HeaderValue, CONTENT_LENGTH},
+    Method, RequestBuilder, StatusCode,
+};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use crate::clock::{Clock, FakeClock};
+use crate::error::{ErrorResponse, Result, SpaceTradersError};
+use crate::fixtures::{Exchange, Fixtures, Recorder, Replay};
+use crate::models::{
+    AgentData, AgentDataResponse, AvailableShips, Cargo, Contract, ContractResult,
+    ContractResultResponse, Cooldown, DeliverResponse, DeliverResult, ExtractResponse,
+    ExtractResult, GetCargoResponse, GetCooldownResponse, GetMarketResponse, GetShipNavResponse,
+    GetShipResponse, JettisonResponse, Market, NavResponse, NavigateResponse, NavigateResult,
+    NegotiateContractResponse, PurchaseShipResponse, PurchaseShipResult, RefuelResponse,
+    RefuelResult, Ship, ShipNav, ShipNavFlightMode, ShipType, ShipsResponse, Survey,
+    SurveyResponse, SurveyResult, TradeResponse, TradeResult, TradeSymbol,
+    ViewAvailableShipsResponse,
+};
+use crate::rate_limit::{self, RateLimitConfig, RateLimiter};
+use crate::retry::{RetryEvent, RetryPolicy};
+
+pub const DEFAULT_BASE_URL: &str = "https://api.spacetraders.io/v2";
+
+/// Longest a request may take end to end before it fails as a timeout,
+/// which the retry policy treats as transient.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Longest to wait for a connection to the server.
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Shared handle to the SpaceTraders API.
+///
+/// Holds one pooled `reqwest::Client` and one rate limiter, so cloning an
+/// `ApiClient` is cheap and every clone shares the same connections and
+/// request budget.
+#[derive(Clone, Debug)]
+pub struct ApiClient {
+    http: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+    limiter: Arc<RateLimiter>,
+    retry: RetryPolicy,
+    fixtures: Option<Arc<Fixtures>>,
+}
+
+impl Default for ApiClient {
+    fn default() -> Self {
+        Self::new(DEFAULT_BASE_URL)
+    }
+}
+
+impl ApiClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        ApiClient {
+            http: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .connect_timeout(CONNECT_TIMEOUT)
+                .build()
+                .expect("HTTP client settings are valid"),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            token: None,
+            limiter: Arc::new(RateLimiter::default()),
+            retry: RetryPolicy::default(),
+            fixtures: None,
+        }
+    }
+
+    /// A client that answers every request from fixtures recorded into `dir`
+    /// and never touches the network. Waits run on a fake clock starting
+    /// when the recording did, so cooldowns and arrivals pass instantly.
+    pub fn replay(dir: impl Into<PathBuf>) -> Result<Self> {
+        let replay = Replay::open(dir)?;
+        let clock = match replay.started_at() {
+            Some(start) => FakeClock::starting_at(start),
+            None => FakeClock::new(),
+        };
+        let limiter = RateLimiter::new(RateLimitConfig::default(), Arc::new(clock));
+        Ok(Self::default()
+            .with_rate_limiter(Arc::new(limiter))
+            .with_fixtures(Fixtures::Replay(replay)))
+    }
+
+    /// Writes every request/response pair to fixture files in `dir`, with
+    /// the bearer token redacted.
+    pub fn recording(self, dir: impl Into<PathBuf>) -> Result<Self> {
+        Ok(self.with_fixtures(Fixtures::Record(Recorder::create(dir)?)))
+    }
+
+    pub fn with_fixtures(mut self, fixtures: Fixtures) -> Self {
+        self.fixtures = Some(Arc::new(fixtures));
+        self
+    }
+
+    pub fn fixtures(&self) -> Option<&Fixtures> {
+        self.fixtures.as_deref()
+    }
+
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    pub fn rate_limiter(&self) -> &Arc<RateLimiter> {
+        &self.limiter
+    }
+
+    /// The clock every wait on behalf of this client should use.
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        self.limiter.clock()
+    }
+
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.set_token(token);
+        self
+    }
+
+    pub fn set_token(&mut self, token: impl Into<String>) {
+        self.token = Some(token.into());
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn request(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> RequestBuilder {
+        let request = self
+            .http
+            .request(method, format!("{}{}", self.base_url, path));
+        let request = match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        };
+        match body {
+            Some(body) => request.json(body),
+            //Add empty content length header
+            None => request.header(CONTENT_LENGTH, HeaderValue::from_static("0")),
+        }
+    }
+
+    pub(crate) async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        self.send(Method::GET, path, None).await
+    }
+
+    pub(crate) async fn post<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<T> {
+        self.send(Method::POST, path, body.as_ref()).await
+    }
+
+    /// Sends a request through the rate limiter, repeating it as the retry
+    /// policy allows.
+    async fn send<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<T> {
+        let mut attempt = 1;
+        loop {
+            match self.send_once(method.clone(), path, body).await {
+                Ok(value) => {
+                    if attempt > 1 {
+                        self.retry.notify(RetryEvent::Recovered {
+                            method: &method,
+                            path,
+                            attempts: attempt,
+                        });
+                    }
+                    return Ok(value);
+                }
+                Err(error) => {
+                    if attempt >= self.retry.max_attempts
+                        || !self.retry.is_retryable(&method, &error)
+                    {
+                        if attempt > 1 {
+                            self.retry.notify(RetryEvent::GaveUp {
+                                method: &method,
+                                path,
+                                attempts: attempt,
+                                error: &error,
+                            });
+                        }
+                        return Err(error);
+                    }
+                    let delay = self.retry.backoff(attempt, &error);
+                    self.retry.notify(RetryEvent::Retrying {
+                        method: &method,
+                        path,
+                        attempt,
+                        error: &error,
+                        delay,
+                    });
+                    self.clock().sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    async fn send_once<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<T> {
+        if let Some(Fixtures::Replay(replay)) = self.fixtures() {
+            let exchange = replay.next(&method, path)?;
+            return self.decode(exchange.status(), &exchange.header_map(), &exchange.body());
+        }
+        self.limiter.acquire().await;
+        let response = self.request(method.clone(), path, body).send().await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        self.limiter.observe(status, &headers).await;
+        let bytes = response.bytes().await?;
+        if let Some(Fixtures::Record(recorder)) = self.fixtures() {
+            recorder.record(&Exchange::capture(
+                &method,
+                path,
+                self.token.as_deref(),
+                body,
+                status,
+                &headers,
+                &bytes,
+                self.clock().utc_now(),
+            ))?;
+        }
+        self.decode(status, &headers, &bytes)
+    }
+
+    /// Turns a response into either the expected body or a typed error,
+    /// decoding the API's error envelope when the request failed.
+    fn decode<T: DeserializeOwned>(
+        &self,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<T> {
+        let retry_after = rate_limit::retry_after(headers, self.clock().utc_now());
+        if status.is_success() {
+            // 204 No Content decodes as `null`, i.e. `None` for optional bodies.
+            let body: &[u8] = if body.is_empty() { b"null" } else { body };
+            return Ok(serde_json::from_slice(body)?);
+        }
+        match serde_json::from_slice::<ErrorResponse>(body) {
+            Ok(envelope) => match SpaceTradersError::from_api(status.as_u16(), envelope.error) {
+                SpaceTradersError::RateLimited { retry_after: None } => {
+                    Err(SpaceTradersError::RateLimited {
+                        retry_after: retry_after.map(|wait| wait.as_secs_f64()),
+                    })
+                }
+                err => Err(err),
+            },
+            Err(_) if status == StatusCode::TOO_MANY_REQUESTS => {
+                Err(SpaceTradersError::RateLimited {
+                    retry_after: retry_after.map(|wait| wait.as_secs_f64()),
+                })
+            }
+            Err(_) => Err(SpaceTradersError::UnexpectedStatus {
+                status: status.as_u16(),
+                body: String::from_utf8_lossy(body).into_owned(),
+            }),
+        }
+    }
+
+    pub async fn register_new_agent(
+        &self,
+        symbol: &str,
+        faction: &str,
+    ) -> Result<serde_json::Value> {
+        let body = json!({
+            "symbol": symbol,
+            "faction": faction,
+        });
+        self.post("/register", Some(body)).await
+    }
+
+    pub async fn get_agent_data(&self) -> Result<AgentData> {
+        let body: AgentDataResponse = self.get("/my/agent").await?;
+        Ok(body.data)
+    }
+
+    pub async fn get_contracts(&self) -> Result<Vec<Contract>> {
+        self.fetch_all("/my/contracts").await
+    }
+
+    pub async fn accept_contract(&self, contract_id: &str) -> Result<ContractResult> {
+        let body: ContractResultResponse = self
+            .post(&format!("/my/contracts/{}/accept", contract_id), None)
+            .await?;
+        Ok(body.data)
+    }
+
+    /// Asks the faction for a new contract. The ship must be docked at a
+    /// waypoint of that faction, e.g. the agent's headquarters.
+    pub async fn negotiate_contract(&self, ship_id: &str) -> Result<Contract> {
+        let body: NegotiateContractResponse = self
+            .post(&format!("/my/ships/{}/negotiate/contract", ship_id), None)
+            .await?;
+        Ok(body.data.contract)
+    }
+
+    /// Hands over `units` of `goods` from the ship's hold towards the contract.
+    /// The ship must be docked at the delivery destination.
+    pub async fn deliver_contract(
+        &self,
+        contract_id: &str,
+        ship_id: &str,
+        goods: &TradeSymbol,
+        units: u32,
+    ) -> Result<DeliverResult> {
+        let body = json!({
+            "shipSymbol": ship_id,
+            "tradeSymbol": goods,
+            "units": units,
+        });
+        let body: DeliverResponse = self
+            .post(
+                &format!("/my/contracts/{}/deliver", contract_id),
+                Some(body),
+            )
+            .await?;
+        Ok(body.data)
+    }
+
+    pub async fn fulfill_contract(&self, contract_id: &str) -> Result<ContractResult> {
+        let body: ContractResultResponse = self
+            .post(&format!("/my/contracts/{}/fulfill", contract_id), None)
+            .await?;
+        Ok(body.data)
+    }
+
+    /// One page of the agent's fleet; `meta.total` says how many ships exist.
+    pub async fn get_my_ships(&self, page: u32, limit: u32) -> Result<ShipsResponse> {
+        self.get(&format!("/my/ships?page={}&limit={}", page, limit))
+            .await
+    }
+
+    pub async fn get_ship(&self, ship_symbol: &str) -> Result<Ship> {
+        let body: GetShipResponse = self.get(&format!("/my/ships/{}", ship_symbol)).await?;
+        Ok(body.data)
+    }
+
+    pub async fn get_ship_cargo(&self, ship_id: &str) -> Result<Cargo> {
+        let body: GetCargoResponse = self.get(&format!("/my/ships/{}/cargo", ship_id)).await?;
+        Ok(body.data)
+    }
+
+    pub async fn sell_goods(
+        &self,
+        ship_id: &str,
+        goods: &TradeSymbol,
+        units: u32,
+    ) -> Result<TradeResult> {
+        let body = json!({
+            "symbol": goods,
+            "units": units,
+        });
+        let body: TradeResponse = self
+            .post(&format!("/my/ships/{}/sell", ship_id), Some(body))
+            .await?;
+        Ok(body.data)
+    }
+
+    pub async fn purchase_goods(
+        &self,
+        ship_id: &str,
+        goods: &TradeSymbol,
+        units: u32,
+    ) -> Result<TradeResult> {
+        let body = json!({
+            "symbol": goods,
+            "units": units,
+        });
+        let body: TradeResponse = self
+            .post(&format!("/my/ships/{}/purchase", ship_id), Some(body))
+            .await?;
+        Ok(body.data)
+    }
+
+    pub async fn jettison_cargo(
+        &self,
+        ship_id: &str,
+        goods: &TradeSymbol,
+        units: u32,
+    ) -> Result<Cargo> {
+        let body = json!({
+            "symbol": goods,
+            "units": units,
+        });
+        let body: JettisonResponse = self
+            .post(&format!("/my/ships/{}/jettison", ship_id), Some(body))
+            .await?;
+        Ok(body.data.cargo)
+    }
+
+    pub async fn get_market(&self, system_symbol: &str, waypoint_symbol: &str) -> Result<Market> {
+        let body: GetMarketResponse = self
+            .get(&format!(
+                "/systems/{}/waypoints/{}/market",
+                system_symbol, waypoint_symbol
+            ))
+            .await?;
+        Ok(body.data)
+    }
+
+    pub async fn navigate_to_waypoint(
+        &self,
+        ship_id: &str,
+        waypoint_symbol: &str,
+    ) -> Result<NavigateResult> {
+        let body = json!({ "waypointSymbol": waypoint_symbol });
+        let body: NavigateResponse = self
+            .post(&format!("/my/ships/{}/navigate", ship_id), Some(body))
+            .await?;
+        Ok(body.data)
+    }
+
+    pub async fn set_flight_mode(
+        &self,
+        ship_id: &str,
+        flight_mode: &ShipNavFlightMode,
+    ) -> Result<ShipNav> {
+        let body = json!({ "flightMode": flight_mode });
+        let body: GetShipNavResponse = self
+            .send(
+                Method::PATCH,
+                &format!("/my/ships/{}/nav", ship_id),
+                Some(&body),
+            )
+            .await?;
+        Ok(body.data)
+    }
+
+    pub async fn send_ship_to_orbit(&self, ship_id: &str) -> Result<ShipNav> {
+        let body: NavResponse = self
+            .post(&format!("/my/ships/{}/orbit", ship_id), None)
+            .await?;
+        Ok(body.data.nav)
+    }
+
+    pub async fn dock_ship(&self, ship_id: &str) -> Result<ShipNav> {
+        let body: NavResponse = self
+            .post(&format!("/my/ships/{}/dock", ship_id), None)
+            .await?;
+        Ok(body.data.nav)
+    }
+
+    pub async fn extract_ores(&self, ship_id: &str) -> Result<ExtractResult> {
+        let body: ExtractResponse = self
+            .post(&format!("/my/ships/{}/extract", ship_id), None)
+            .await?;
+        Ok(body.data)
+    }
+
+    /// Extracts with `survey`, biasing the yield towards its deposits.
+    pub async fn extract_with_survey(
+        &self,
+        ship_id: &str,
+        survey: &Survey,
+    ) -> Result<ExtractResult> {
+        let body: ExtractResponse = self
+            .post(
+                &format!("/my/ships/{}/extract/survey", ship_id),
+                Some(serde_json::to_value(survey)?),
+            )
+            .await?;
+        Ok(body.data)
+    }
+
+    pub async fn create_survey(&self, ship_id: &str) -> Result<SurveyResult> {
+        let body: SurveyResponse = self
+            .post(&format!("/my/ships/{}/survey", ship_id), None)
+            .await?;
+        Ok(body.data)
+    }
+
+    /// The ship's active cooldown, or `None` if it is ready to act.
+    pub async fn get_ship_cooldown(&self, ship_id: &str) -> Result<Option<Cooldown>> {
+        let body: Option<GetCooldownResponse> =
+            self.get(&format!("/my/ships/{}/cooldown", ship_id)).await?;
+        Ok(body.map(|body| body.data))
+    }
+
+    pub async fn refuel_ship(&self, ship_id: &str) -> Result<RefuelResult> {
+        let body: RefuelResponse = self
+            .post(&format!("/my/ships/{}/refuel", ship_id), None)
+            .await?;
+        Ok(body.data)
+    }
+
+    pub async fn find_ships_at_shipyard(
+        &self,
+        system_symbol: &str,
+        shipyard_symbol: &str,
+    ) -> Result<AvailableShips> {
+        let body: ViewAvailableShipsResponse = self
+            .get(&format!(
+                "/systems/{}/waypoints/{}/shipyard",
+                system_symbol, shipyard_symbol
+            ))
+            .await?;
+        Ok(body.data)
+    }
+
+    pub async fn buy_ship(
+        &self,
+        waypoint_symbol: &str,
+        ship_type: &ShipType,
+    ) -> Result<PurchaseShipResult> {
+        let body = json!({
+            "shipType": ship_type,
+            "waypointSymbol": waypoint_symbol,
+        });
+        let body: PurchaseShipResponse = self.post("/my/ships", Some(body)).await?;
+        Ok(body.data)
+    }
+}
@@ -0,0 +1,143 @@
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Source of time for anything that waits: the rate limiter, cooldown
+/// scheduling and navigation. Swapping in a `FakeClock` lets those be driven
+/// without real sleeps.
+pub trait Clock: Send + Sync + fmt::Debug {
+    fn now(&self) -> Instant;
+    /// Wall-clock time, for comparing against timestamps from the API.
+    fn utc_now(&self) -> DateTime<Utc>;
+    fn sleep(&self, duration: Duration) -> BoxFuture<'_, ()>;
+
+    fn sleep_until(&self, deadline: Instant) -> BoxFuture<'_, ()> {
+        let duration = deadline.saturating_duration_since(self.now());
+        self.sleep(duration)
+    }
+
+    fn sleep_until_utc(&self, deadline: DateTime<Utc>) -> BoxFuture<'_, ()> {
+        let duration = (deadline - self.utc_now()).to_std().unwrap_or_default();
+        self.sleep(duration)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn utc_now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'_, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// A clock that only moves when told to. `sleep` advances the clock by the
+/// requested duration and returns immediately, so code under test observes
+/// exactly the waits it asked for.
+#[derive(Debug)]
+pub struct FakeClock {
+    start: Instant,
+    start_utc: DateTime<Utc>,
+    elapsed: Mutex<Duration>,
+}
+
+impl Default for FakeClock {
+    fn default() -> Self {
+        Self::starting_at(Utc::now())
+    }
+}
+
+impl FakeClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A clock whose wall time starts at `start_utc`.
+    pub fn starting_at(start_utc: DateTime<Utc>) -> Self {
+        FakeClock {
+            start: Instant::now(),
+            start_utc,
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+    }
+
+    /// Total time that has passed on this clock since it was created.
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap()
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn utc_now(&self) -> DateTime<Utc> {
+        self.start_utc + chrono::Duration::from_std(self.elapsed()).unwrap_or_default()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'_, ()> {
+        self.advance(duration);
+        Box::pin(std::future::ready(()))
+    }
+}
+
+/// Tokio's notion of time, anchored to a wall-clock start.
+///
+/// On a runtime whose time is paused (`start_paused`), tokio jumps straight
+/// to the next timer whenever every task is waiting, so concurrent sleeps
+/// overlap the way they would in real time while hours of game time pass in
+/// moments. On a normal runtime this behaves like `SystemClock`.
+#[derive(Debug, Clone, Copy)]
+pub struct TokioClock {
+    start: tokio::time::Instant,
+    start_utc: DateTime<Utc>,
+}
+
+impl TokioClock {
+    /// Must be created inside the runtime whose time it should follow.
+    pub fn starting_at(start_utc: DateTime<Utc>) -> Self {
+        TokioClock {
+            start: tokio::time::Instant::now(),
+            start_utc,
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+impl Clock for TokioClock {
+    fn now(&self) -> Instant {
+        tokio::time::Instant::now().into_std()
+    }
+
+    fn utc_now(&self) -> DateTime<Utc> {
+        self.start_utc + chrono::Duration::from_std(self.elapsed()).unwrap_or_default()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'_, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
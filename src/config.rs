@@ -0,0 +1,488 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use serde::Deserialize;
+
+use crate::client::DEFAULT_BASE_URL;
+use crate::contract_evaluator::EvaluatorConfig;
+use crate::error::{Result, SpaceTradersError};
+use crate::fleet::{Behaviour, FleetConfig, PurchaseTarget};
+use crate::mining::MiningConfig;
+use crate::models::{ShipRole, ShipType, TradeSymbol};
+use crate::rate_limit::RateLimitConfig;
+
+/// Read when no config path is given, if it exists.
+pub const DEFAULT_CONFIG_PATH: &str = "spacetraders.toml";
+
+/// Length of the random part of generated agent symbols.
+const RANDOM_SYMBOL_LEN: usize = 8;
+
+/// Everything about how the bot plays that isn't a secret. Loaded from a
+/// TOML file, with `SPACETRADERS_*` environment variables taking precedence;
+/// every section and field is optional. The agent token stays in `TOKEN`.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub api: ApiSettings,
+    pub agent: AgentSettings,
+    pub fleet: FleetSettings,
+    pub mining: MiningSettings,
+    pub contracts: ContractSettings,
+    pub rate_limit: RateLimitSettings,
+    pub logging: LoggingSettings,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiSettings {
+    pub base_url: String,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        ApiSettings {
+            base_url: DEFAULT_BASE_URL.to_string(),
+        }
+    }
+}
+
+/// Who `register` signs up as.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct AgentSettings {
+    /// Registers under a random symbol starting with `symbol_prefix` if unset.
+    pub symbol: Option<String>,
+    pub symbol_prefix: String,
+    pub faction: String,
+}
+
+impl Default for AgentSettings {
+    fn default() -> Self {
+        AgentSettings {
+            symbol: None,
+            symbol_prefix: "SFP-".to_string(),
+            faction: "COSMIC".to_string(),
+        }
+    }
+}
+
+/// What `run` does with the fleet. Unset fields keep `FleetConfig`'s
+/// defaults; role assignments are merged over the default ones.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct FleetSettings {
+    /// The system to work in; the headquarters' system if unset.
+    pub home_system: Option<String>,
+    /// Credits the purchase planner never spends.
+    pub credit_reserve: u64,
+    pub roles: HashMap<ShipRole, Behaviour>,
+    pub ships: HashMap<String, Behaviour>,
+    pub default_behaviour: Option<Behaviour>,
+    pub without_contract: Option<Behaviour>,
+    pub refresh_interval_secs: Option<u64>,
+    /// Replaces the default purchases; an empty list buys nothing.
+    pub purchases: Option<Vec<PurchaseTarget>>,
+    /// Where market snapshots and transactions are kept between runs.
+    pub market_store: PathBuf,
+}
+
+impl Default for FleetSettings {
+    fn default() -> Self {
+        FleetSettings {
+            home_system: None,
+            credit_reserve: 25_000,
+            roles: HashMap::new(),
+            ships: HashMap::new(),
+            default_behaviour: None,
+            without_contract: None,
+            refresh_interval_secs: None,
+            purchases: None,
+            market_store: PathBuf::from("market_store.jsonl"),
+        }
+    }
+}
+
+impl FleetSettings {
+    pub fn fleet_config(&self) -> FleetConfig {
+        let mut config = FleetConfig::default();
+        config.roles.extend(self.roles.clone());
+        config.ships.extend(self.ships.clone());
+        if let Some(behaviour) = self.default_behaviour {
+            config.default_behaviour = behaviour;
+        }
+        if let Some(behaviour) = self.without_contract {
+            config.without_contract = behaviour;
+        }
+        if let Some(seconds) = self.refresh_interval_secs {
+            config.refresh_interval = Duration::from_secs(seconds);
+        }
+        if let Some(purchases) = &self.purchases {
+            config.purchases = purchases.clone();
+        }
+        config
+    }
+}
+
+/// Which goods miners keep and throw away; everything else is sold.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct MiningSettings {
+    pub keep: Vec<TradeSymbol>,
+    pub jettison: Vec<TradeSymbol>,
+    pub targets: Vec<TradeSymbol>,
+}
+
+impl MiningSettings {
+    pub fn mining_config(&self) -> MiningConfig {
+        MiningConfig {
+            keep: self.keep.clone(),
+            jettison: self.jettison.clone(),
+            targets: self.targets.clone(),
+        }
+    }
+}
+
+/// What a contract must be worth before it is accepted. Unset fields keep
+/// `EvaluatorConfig`'s defaults.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ContractSettings {
+    pub min_margin: Option<f64>,
+    pub credits_per_hour: Option<u64>,
+    pub default_fuel_price: Option<u64>,
+}
+
+impl ContractSettings {
+    pub fn evaluator_config(&self) -> EvaluatorConfig {
+        let default = EvaluatorConfig::default();
+        EvaluatorConfig {
+            min_margin: self.min_margin.unwrap_or(default.min_margin),
+            credits_per_hour: self.credits_per_hour.unwrap_or(default.credits_per_hour),
+            default_fuel_price: self
+                .default_fuel_price
+                .unwrap_or(default.default_fuel_price),
+        }
+    }
+}
+
+/// Overrides for the client's request budget, for accounts with a
+/// different limit than the default one.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitSettings {
+    pub per_second: Option<f64>,
+    pub burst: Option<u32>,
+    pub burst_window_secs: Option<u64>,
+}
+
+impl RateLimitSettings {
+    pub fn rate_limit_config(&self) -> RateLimitConfig {
+        let default = RateLimitConfig::default();
+        RateLimitConfig {
+            per_second: self.per_second.unwrap_or(default.per_second),
+            burst: self.burst.unwrap_or(default.burst),
+            burst_window: self
+                .burst_window_secs
+                .map_or(default.burst_window, Duration::from_secs),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingSettings {
+    /// Print each retry and how it ended.
+    pub retries: bool,
+    /// Record every API exchange as fixture files in this directory.
+    pub record_dir: Option<PathBuf>,
+}
+
+impl Default for LoggingSettings {
+    fn default() -> Self {
+        LoggingSettings {
+            retries: true,
+            record_dir: None,
+        }
+    }
+}
+
+fn invalid(message: impl Into<String>) -> SpaceTradersError {
+    SpaceTradersError::Config {
+        message: message.into(),
+    }
+}
+
+impl Config {
+    /// Reads `path`, or `DEFAULT_CONFIG_PATH` if it exists, applies the
+    /// environment overrides and checks the result.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let mut config = match path {
+            Some(path) => Self::read(path)?,
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => {
+                Self::read(Path::new(DEFAULT_CONFIG_PATH))?
+            }
+            None => Config::default(),
+        };
+        config.apply_env(|name| std::env::var(name).ok())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| invalid(format!("can't read {}: {}", path.display(), err)))?;
+        Self::parse(&text).map_err(|err| invalid(format!("{}: {}", path.display(), err)))
+    }
+
+    pub fn parse(text: &str) -> std::result::Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    /// Overrides settings from `SPACETRADERS_*` variables, looked up with
+    /// `var`.
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<()> {
+        fn parsed<T: FromStr>(name: &str, value: String) -> Result<T> {
+            value
+                .parse()
+                .map_err(|_| invalid(format!("{}: `{}` is not a valid value", name, value)))
+        }
+
+        if let Some(url) = var("SPACETRADERS_API_URL") {
+            self.api.base_url = url;
+        }
+        if let Some(symbol) = var("SPACETRADERS_AGENT_SYMBOL") {
+            self.agent.symbol = Some(symbol);
+        }
+        if let Some(faction) = var("SPACETRADERS_FACTION") {
+            self.agent.faction = faction;
+        }
+        if let Some(system) = var("SPACETRADERS_HOME_SYSTEM") {
+            self.fleet.home_system = Some(system);
+        }
+        if let Some(reserve) = var("SPACETRADERS_CREDIT_RESERVE") {
+            self.fleet.credit_reserve = parsed("SPACETRADERS_CREDIT_RESERVE", reserve)?;
+        }
+        if let Some(per_second) = var("SPACETRADERS_RATE_LIMIT") {
+            self.rate_limit.per_second = Some(parsed("SPACETRADERS_RATE_LIMIT", per_second)?);
+        }
+        if let Some(dir) = var("SPACETRADERS_RECORD") {
+            self.logging.record_dir = Some(PathBuf::from(dir));
+        }
+        Ok(())
+    }
+
+    /// Rejects settings the bot would only trip over later, naming the
+    /// offending field.
+    pub fn validate(&self) -> Result<()> {
+        let url = &self.api.base_url;
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(invalid(format!(
+                "api.base_url: `{}` is not an http(s) URL",
+                url
+            )));
+        }
+
+        let agent = &self.agent;
+        if let Some(symbol) = &agent.symbol {
+            if !(3..=14).contains(&symbol.len()) {
+                return Err(invalid(format!(
+                    "agent.symbol: `{}` must be 3 to 14 characters",
+                    symbol
+                )));
+            }
+        }
+        if agent.symbol_prefix.len() + RANDOM_SYMBOL_LEN > 14 {
+            return Err(invalid(format!(
+                "agent.symbol_prefix: `{}` leaves no room for the random part; use at most {} characters",
+                agent.symbol_prefix,
+                14 - RANDOM_SYMBOL_LEN
+            )));
+        }
+        if agent.faction.is_empty() {
+            return Err(invalid("agent.faction must not be empty"));
+        }
+
+        let fleet = &self.fleet;
+        if fleet.home_system.as_deref() == Some("") {
+            return Err(invalid("fleet.home_system must not be empty"));
+        }
+        if let Some(role) = fleet
+            .roles
+            .keys()
+            .find(|role| matches!(role, ShipRole::Unknown(_)))
+        {
+            return Err(invalid(format!(
+                "fleet.roles: unknown ship role `{}`",
+                role
+            )));
+        }
+        if fleet.refresh_interval_secs == Some(0) {
+            return Err(invalid("fleet.refresh_interval_secs must be positive"));
+        }
+        for purchase in fleet.purchases.iter().flatten() {
+            if let ShipType::Unknown(ship_type) = &purchase.ship_type {
+                return Err(invalid(format!(
+                    "fleet.purchases: unknown ship type `{}`",
+                    ship_type
+                )));
+            }
+        }
+
+        let mining = &self.mining;
+        for (field, goods) in [
+            ("keep", &mining.keep),
+            ("jettison", &mining.jettison),
+            ("targets", &mining.targets),
+        ] {
+            if let Some(TradeSymbol::Unknown(good)) = goods
+                .iter()
+                .find(|good| matches!(good, TradeSymbol::Unknown(_)))
+            {
+                return Err(invalid(format!(
+                    "mining.{}: unknown good `{}`",
+                    field, good
+                )));
+            }
+        }
+        if let Some(good) = mining
+            .keep
+            .iter()
+            .find(|good| mining.jettison.contains(good))
+        {
+            return Err(invalid(format!(
+                "mining: `{}` is on both the keep and the jettison list",
+                good
+            )));
+        }
+
+        let contracts = self.contracts.evaluator_config();
+        if !contracts.min_margin.is_finite() || contracts.min_margin >= 1.0 {
+            return Err(invalid(
+                "contracts.min_margin must be a share of the payment below 1.0",
+            ));
+        }
+
+        let rate_limit = self.rate_limit.rate_limit_config();
+        if rate_limit.per_second.is_nan() || rate_limit.per_second <= 0.0 {
+            return Err(invalid("rate_limit.per_second must be positive"));
+        }
+        if rate_limit.burst == 0 {
+            return Err(invalid("rate_limit.burst must be positive"));
+        }
+        if rate_limit.burst_window.is_zero() {
+            return Err(invalid("rate_limit.burst_window_secs must be positive"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    /// The message of the `Config` error `validate` gives for `text`.
+    fn rejection(text: &str) -> String {
+        match Config::parse(text).unwrap().validate() {
+            Err(SpaceTradersError::Config { message }) => message,
+            other => panic!("expected a config error for {:?}, got {:?}", text, other),
+        }
+    }
+
+    #[test]
+    fn example_config_parses_and_validates() {
+        let config = Config::parse(include_str!("../spacetraders.example.toml")).unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.fleet.credit_reserve, 25_000);
+        assert_eq!(config.rate_limit.burst, Some(30));
+        assert_eq!(config.contracts.evaluator_config().min_margin, 0.1);
+        assert_eq!(
+            config.fleet.fleet_config().roles[&ShipRole::Hauler],
+            Behaviour::Trader
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let err = Config::parse("[fleet]\ncredit_reserv = 100\n").unwrap_err();
+        assert!(err.to_string().contains("credit_reserv"), "{}", err);
+        assert!(Config::parse("[fleets]\n").is_err());
+    }
+
+    #[test]
+    fn validate_names_the_offending_field() {
+        for (text, field) in [
+            ("[api]\nbase_url = \"ftp://example.com\"", "api.base_url"),
+            ("[agent]\nsymbol = \"AB\"", "agent.symbol"),
+            ("[agent]\nsymbol_prefix = \"TOO-LONG-\"", "agent.symbol_prefix"),
+            ("[agent]\nfaction = \"\"", "agent.faction"),
+            ("[fleet]\nhome_system = \"\"", "fleet.home_system"),
+            ("[fleet.roles]\nFREIGHTER = \"miner\"", "fleet.roles"),
+            ("[fleet]\nrefresh_interval_secs = 0", "fleet.refresh_interval_secs"),
+            (
+                "[[fleet.purchases]]\nship_type = \"SHIP_BATHTUB\"\nbehaviour = \"miner\"\ncount = 1",
+                "fleet.purchases",
+            ),
+            ("[mining]\ntargets = [\"MOON_CHEESE\"]", "mining.targets"),
+            (
+                "[mining]\nkeep = [\"IRON_ORE\"]\njettison = [\"IRON_ORE\"]",
+                "mining:",
+            ),
+            ("[contracts]\nmin_margin = 1.5", "contracts.min_margin"),
+            ("[rate_limit]\nper_second = 0.0", "rate_limit.per_second"),
+            ("[rate_limit]\nper_second = -2.0", "rate_limit.per_second"),
+            ("[rate_limit]\nburst = 0", "rate_limit.burst"),
+            ("[rate_limit]\nburst_window_secs = 0", "rate_limit.burst_window_secs"),
+        ] {
+            let message = rejection(text);
+            assert!(message.starts_with(field), "{:?}: {}", text, message);
+        }
+    }
+
+    #[test]
+    fn environment_overrides_the_file() {
+        let mut config = Config::parse(
+            "[fleet]\nhome_system = \"X1-FILE\"\ncredit_reserve = 100\n[rate_limit]\nper_second = 2.0\n",
+        )
+        .unwrap();
+        config
+            .apply_env(env(&[
+                ("SPACETRADERS_HOME_SYSTEM", "X1-ENV"),
+                ("SPACETRADERS_CREDIT_RESERVE", "500"),
+                ("SPACETRADERS_RATE_LIMIT", "1.5"),
+                ("SPACETRADERS_API_URL", "http://localhost:8080/v2"),
+            ]))
+            .unwrap();
+
+        assert_eq!(config.fleet.home_system.as_deref(), Some("X1-ENV"));
+        assert_eq!(config.fleet.credit_reserve, 500);
+        assert_eq!(config.rate_limit.per_second, Some(1.5));
+        assert_eq!(config.api.base_url, "http://localhost:8080/v2");
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn unparsable_environment_values_are_config_errors() {
+        for name in ["SPACETRADERS_CREDIT_RESERVE", "SPACETRADERS_RATE_LIMIT"] {
+            let mut config = Config::default();
+            match config.apply_env(env(&[(name, "lots")])) {
+                Err(SpaceTradersError::Config { message }) => {
+                    assert!(message.starts_with(name), "{}", message)
+                }
+                other => panic!("expected a config error for {}, got {:?}", name, other),
+            }
+        }
+    }
+}
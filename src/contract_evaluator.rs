@@ -0,0 +1,378 @@
+use std::fmt;
+
+use crate::contracts::{plan_contract, DeliveryPlan, Source};
+use crate::error::Result;
+use crate::fleet::FleetContext;
+use crate::models::{Contract, Ship, TradeSymbol};
+
+/// Units of fuel in the tank per unit of FUEL bought at a market.
+const FUEL_PER_MARKET_UNIT: u64 = 100;
+
+/// What a contract has to be worth before it is accepted.
+#[derive(Debug, Clone)]
+pub struct EvaluatorConfig {
+    /// Profit required as a share of the total payment, e.g. `0.1` for 10%.
+    pub min_margin: f64,
+    /// What an hour of ship time is worth doing something else.
+    pub credits_per_hour: u64,
+    /// Price of one market unit of fuel when none has been observed yet.
+    pub default_fuel_price: u64,
+}
+
+impl Default for EvaluatorConfig {
+    fn default() -> Self {
+        EvaluatorConfig {
+            min_margin: 0.1,
+            credits_per_hour: 10_000,
+            default_fuel_price: 80,
+        }
+    }
+}
+
+/// The estimated costs of completing a contract with one ship, against what
+/// it pays.
+#[derive(Debug, Clone)]
+pub struct ContractEvaluation {
+    pub contract_id: String,
+    pub ship_symbol: String,
+    pub payment: u64,
+    pub purchase_cost: u64,
+    pub fuel_cost: u64,
+    pub seconds: u64,
+    pub opportunity_cost: u64,
+    pub meets_deadline: bool,
+    pub min_margin: f64,
+}
+
+impl ContractEvaluation {
+    pub fn total_cost(&self) -> u64 {
+        self.purchase_cost + self.fuel_cost + self.opportunity_cost
+    }
+
+    pub fn profit(&self) -> i64 {
+        self.payment as i64 - self.total_cost() as i64
+    }
+
+    /// Profit as a share of the payment.
+    pub fn margin(&self) -> f64 {
+        if self.payment == 0 {
+            return 0.0;
+        }
+        self.profit() as f64 / self.payment as f64
+    }
+
+    pub fn is_acceptable(&self) -> bool {
+        self.meets_deadline && self.margin() >= self.min_margin
+    }
+}
+
+impl fmt::Display for ContractEvaluation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "contract {} with {}: payment {}, purchases {}, fuel {}, ship time {}s ({}), \
+            profit {} ({:.1}% margin, need {:.1}%){}",
+            self.contract_id,
+            self.ship_symbol,
+            self.payment,
+            self.purchase_cost,
+            self.fuel_cost,
+            self.seconds,
+            self.opportunity_cost,
+            self.profit(),
+            self.margin() * 100.0,
+            self.min_margin * 100.0,
+            if self.meets_deadline {
+                ""
+            } else {
+                ", misses deadline"
+            }
+        )
+    }
+}
+
+/// Weighs contracts against the fleet and the market store before they are
+/// accepted.
+#[derive(Debug, Clone, Default)]
+pub struct ContractEvaluator {
+    pub config: EvaluatorConfig,
+}
+
+impl ContractEvaluator {
+    pub fn new(config: EvaluatorConfig) -> Self {
+        ContractEvaluator { config }
+    }
+
+    fn fuel_price(&self, context: &FleetContext) -> u64 {
+        context
+            .market_store
+            .lock()
+            .unwrap()
+            .best_purchase_market(&context.home_system, &TradeSymbol::Fuel)
+            .map_or(self.config.default_fuel_price, |best| best.purchase_price)
+    }
+
+    fn evaluate_plans(
+        &self,
+        context: &FleetContext,
+        ship: &Ship,
+        contract: &Contract,
+        plans: &[DeliveryPlan],
+    ) -> ContractEvaluation {
+        let purchase_cost = plans
+            .iter()
+            .map(|plan| match &plan.source {
+                Source::Buy { price_per_unit, .. } => price_per_unit * plan.units,
+                Source::Mine { .. } => 0,
+            })
+            .sum();
+        let fuel: u64 = plans.iter().map(|plan| plan.fuel).sum();
+        let seconds: u64 = plans.iter().map(|plan| plan.seconds).sum();
+        let now = context.client().clock().utc_now();
+        let payment = &contract.terms.payment;
+        ContractEvaluation {
+            contract_id: contract.id.clone(),
+            ship_symbol: ship.symbol.clone(),
+            payment: payment.payment_on_accepted + payment.payment_on_fulfilled,
+            purchase_cost,
+            fuel_cost: fuel.div_ceil(FUEL_PER_MARKET_UNIT) * self.fuel_price(context),
+            seconds,
+            opportunity_cost: seconds * self.config.credits_per_hour / 3600,
+            meets_deadline: now + chrono::Duration::seconds(seconds as i64)
+                < contract.terms.deadline,
+            min_margin: self.config.min_margin,
+        }
+    }
+
+    /// The most profitable way for one of `ships` to complete `contract`, or
+    /// `None` if none of them can.
+    pub async fn evaluate(
+        &self,
+        context: &FleetContext,
+        ships: &[Ship],
+        contract: &Contract,
+    ) -> Result<Option<ContractEvaluation>> {
+        let mut best: Option<ContractEvaluation> = None;
+        for ship in ships {
+            let Some(plans) = plan_contract(context, ship, contract).await? else {
+                continue;
+            };
+            let evaluation = self.evaluate_plans(context, ship, contract, &plans);
+            let better = best.as_ref().is_none_or(|best| {
+                (evaluation.meets_deadline, evaluation.profit())
+                    > (best.meets_deadline, best.profit())
+            });
+            if better {
+                best = Some(evaluation);
+            }
+        }
+        Ok(best)
+    }
+
+    /// Accepts `contract` if it can still be accepted and is worth it,
+    /// printing the breakdown either way. Returns whether the contract is
+    /// now accepted.
+    pub async fn accept_if_profitable(
+        &self,
+        context: &FleetContext,
+        ships: &[Ship],
+        contract: &Contract,
+    ) -> Result<bool> {
+        if contract.accepted {
+            return Ok(true);
+        }
+        if contract.deadline_to_accept <= context.client().clock().utc_now() {
+            println!("Contract {} can no longer be accepted", contract.id);
+            return Ok(false);
+        }
+        let Some(evaluation) = self.evaluate(context, ships, contract).await? else {
+            println!(
+                "Declining contract {}: no ship can complete it",
+                contract.id
+            );
+            return Ok(false);
+        };
+        if !evaluation.is_acceptable() {
+            println!("Declining {}", evaluation);
+            return Ok(false);
+        }
+        let result = context.client().accept_contract(&contract.id).await?;
+        println!("Accepted {}", evaluation);
+        Ok(result.contract.accepted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, Utc};
+
+    use super::*;
+    use crate::fleet;
+    use crate::mock_server::MockServer;
+    use crate::models::{Market, MarketTradeGood, MarketTradeGoodType, SupplyLevel};
+
+    async fn start() -> (MockServer, FleetContext, DateTime<Utc>) {
+        let server = fleet::tests::start().await;
+        let context = fleet::tests::mock_context(&server).await;
+        let now = context.client().clock().utc_now();
+        (server, context, now)
+    }
+
+    /// The mock contract, paying 10,000 in total and due an hour from `now`.
+    async fn contract(server: &MockServer, now: DateTime<Utc>) -> (Ship, Contract) {
+        let ship = server.client().get_ship("MOCK-AGENT-1").await.unwrap();
+        let mut contract = server.state().contracts()[0].clone();
+        contract.terms.payment.payment_on_accepted = 2_000;
+        contract.terms.payment.payment_on_fulfilled = 8_000;
+        contract.terms.deadline = now + Duration::hours(1);
+        (ship, contract)
+    }
+
+    fn plan(source: Source, seconds: u64, fuel: u64) -> DeliveryPlan {
+        DeliveryPlan {
+            trade_symbol: TradeSymbol::IronOre,
+            units: 10,
+            source,
+            destination: "X1-MOCK-A1".to_string(),
+            trips: 1,
+            seconds,
+            fuel,
+        }
+    }
+
+    fn evaluation(payment: u64, total_cost: u64) -> ContractEvaluation {
+        ContractEvaluation {
+            contract_id: "contract".to_string(),
+            ship_symbol: "SHIP-1".to_string(),
+            payment,
+            purchase_cost: total_cost,
+            fuel_cost: 0,
+            seconds: 0,
+            opportunity_cost: 0,
+            meets_deadline: true,
+            min_margin: 0.1,
+        }
+    }
+
+    #[test]
+    fn margin_is_checked_against_the_minimum() {
+        assert_eq!(evaluation(1_000, 850).margin(), 0.15);
+        assert!(evaluation(1_000, 850).is_acceptable());
+        assert!(evaluation(1_000, 900).is_acceptable());
+        assert!(!evaluation(1_000, 950).is_acceptable());
+        assert!(!evaluation(1_000, 1_200).is_acceptable());
+        assert!(!evaluation(0, 0).is_acceptable());
+    }
+
+    #[tokio::test]
+    async fn costs_purchases_fuel_and_ship_time() {
+        let (server, context, now) = start().await;
+        let (ship, contract) = contract(&server, now).await;
+        let evaluator = ContractEvaluator::default();
+        let plans = [
+            plan(
+                Source::Buy {
+                    market: "X1-MOCK-A1".to_string(),
+                    price_per_unit: 30,
+                },
+                1_200,
+                150,
+            ),
+            plan(
+                Source::Mine {
+                    asteroid: "X1-MOCK-B1".to_string(),
+                },
+                600,
+                100,
+            ),
+        ];
+
+        let evaluation = evaluator.evaluate_plans(&context, &ship, &contract, &plans);
+        assert_eq!(evaluation.payment, 10_000);
+        assert_eq!(evaluation.purchase_cost, 300);
+        // 250 units of fuel is three market units at the default price.
+        assert_eq!(evaluation.fuel_cost, 3 * 80);
+        // Half an hour at 10,000 credits an hour.
+        assert_eq!(evaluation.seconds, 1_800);
+        assert_eq!(evaluation.opportunity_cost, 5_000);
+        assert_eq!(evaluation.profit(), 10_000 - 300 - 240 - 5_000);
+        assert!(evaluation.meets_deadline);
+        assert!(evaluation.is_acceptable());
+
+        let strict = ContractEvaluator::new(EvaluatorConfig {
+            min_margin: 0.5,
+            ..EvaluatorConfig::default()
+        });
+        assert!(!strict
+            .evaluate_plans(&context, &ship, &contract, &plans)
+            .is_acceptable());
+    }
+
+    #[tokio::test]
+    async fn fuel_is_priced_at_the_cheapest_known_market() {
+        let (server, context, now) = start().await;
+        let (ship, contract) = contract(&server, now).await;
+        let fuel = Market {
+            symbol: "X1-MOCK-A1".to_string(),
+            exports: Vec::new(),
+            imports: Vec::new(),
+            exchange: Vec::new(),
+            transactions: Vec::new(),
+            trade_goods: vec![MarketTradeGood {
+                symbol: TradeSymbol::Fuel,
+                trade_type: MarketTradeGoodType::Exchange,
+                trade_volume: 100,
+                supply: SupplyLevel::Moderate,
+                activity: None,
+                purchase_price: 120,
+                sell_price: 100,
+            }],
+        };
+        context
+            .market_store
+            .lock()
+            .unwrap()
+            .record_market_at(&fuel, now)
+            .unwrap();
+        let plans = [plan(
+            Source::Mine {
+                asteroid: "X1-MOCK-B1".to_string(),
+            },
+            0,
+            250,
+        )];
+
+        let evaluation =
+            ContractEvaluator::default().evaluate_plans(&context, &ship, &contract, &plans);
+        assert_eq!(evaluation.fuel_cost, 3 * 120);
+    }
+
+    #[tokio::test]
+    async fn contracts_finishing_after_the_deadline_are_rejected() {
+        let (server, context, now) = start().await;
+        let (ship, contract) = contract(&server, now).await;
+        let evaluator = ContractEvaluator::new(EvaluatorConfig {
+            credits_per_hour: 0,
+            ..EvaluatorConfig::default()
+        });
+        let mine = |seconds| {
+            plan(
+                Source::Mine {
+                    asteroid: "X1-MOCK-B1".to_string(),
+                },
+                seconds,
+                0,
+            )
+        };
+
+        let late = evaluator.evaluate_plans(&context, &ship, &contract, &[mine(7_200)]);
+        assert!(!late.meets_deadline);
+        assert_eq!(late.profit(), 10_000);
+        assert!(!late.is_acceptable());
+        assert!(late.to_string().ends_with("misses deadline"));
+
+        let on_time = evaluator.evaluate_plans(&context, &ship, &contract, &[mine(3_000)]);
+        assert!(on_time.meets_deadline);
+        assert!(on_time.is_acceptable());
+    }
+}
@@ -0,0 +1,397 @@
+use chrono::{DateTime, Utc};
+
+use crate::error::Result;
+use crate::fleet::{is_active, FleetContext};
+use crate::mining::{extract_once, home_asteroid};
+use crate::models::{Contract, Delivery, Ship, TradeSymbol, Waypoint};
+use crate::routing::{Route, ShipSpecs};
+use crate::trading::record_market;
+
+/// Rough time it takes to mine one unit of a specific good: a ~70s cooldown
+/// yields a handful of units, not all of them the good that's wanted.
+pub const MINING_SECONDS_PER_UNIT: u64 = 10;
+
+/// Extractions a contract runner makes per unit it needs before giving up
+/// on mining a good, in case the asteroid turns out not to yield it.
+const MAX_EXTRACTIONS_PER_UNIT: u32 = 2;
+
+/// Where the goods for a delivery come from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    Buy { market: String, price_per_unit: u64 },
+    Mine { asteroid: String },
+}
+
+impl Source {
+    pub fn waypoint(&self) -> &str {
+        match self {
+            Source::Buy { market, .. } => market,
+            Source::Mine { asteroid } => asteroid,
+        }
+    }
+}
+
+/// How one ship would complete one delivery of a contract.
+#[derive(Debug, Clone)]
+pub struct DeliveryPlan {
+    pub trade_symbol: TradeSymbol,
+    pub units: u64,
+    pub source: Source,
+    pub destination: String,
+    /// Round trips between source and destination, one per hold load.
+    pub trips: u64,
+    pub seconds: u64,
+    pub fuel: u64,
+}
+
+pub(crate) fn units_held(ship: &Ship, symbol: &TradeSymbol) -> u32 {
+    ship.cargo
+        .inventory
+        .iter()
+        .filter(|cargo| &cargo.symbol == symbol)
+        .map(|cargo| cargo.units)
+        .sum()
+}
+
+/// Whether `ship` can mine `symbol` at `asteroid`: one of its mounts lists
+/// it as a deposit, or one of the asteroid's deposit traits yields it.
+fn extractable(ship: &Ship, asteroid: &Waypoint, symbol: &TradeSymbol) -> bool {
+    let mounted = ship.mounts.iter().any(|mount| {
+        mount
+            .deposits
+            .iter()
+            .flatten()
+            .any(|deposit| deposit == symbol.as_str())
+    });
+    mounted || asteroid.has_deposit(symbol)
+}
+
+/// Where `ship` can get `symbol` in its system: the cheapest known market,
+/// or the home asteroid if nobody is known to sell it and it can be mined
+/// there.
+pub async fn find_source(
+    context: &FleetContext,
+    ship: &Ship,
+    symbol: &TradeSymbol,
+) -> Result<Option<Source>> {
+    let cheapest = context
+        .market_store
+        .lock()
+        .unwrap()
+        .best_purchase_market(&ship.nav.system_symbol, symbol)
+        .map(|best| Source::Buy {
+            market: best.waypoint_symbol.clone(),
+            price_per_unit: best.purchase_price,
+        });
+    if cheapest.is_some() {
+        return Ok(cheapest);
+    }
+    let Some(asteroid) = home_asteroid(context).await? else {
+        return Ok(None);
+    };
+    let waypoint = context
+        .client()
+        .get_waypoint(&context.home_system, &asteroid)
+        .await?;
+    if !extractable(ship, &waypoint, symbol) {
+        return Ok(None);
+    }
+    Ok(Some(Source::Mine { asteroid }))
+}
+
+/// Plans `delivery` for `ship` starting at `from`, or `None` if the ship has
+/// no hold, the goods have no known source or a leg can't be flown.
+pub async fn plan_delivery(
+    context: &FleetContext,
+    ship: &Ship,
+    from: &str,
+    delivery: &Delivery,
+) -> Result<Option<DeliveryPlan>> {
+    let units = delivery.units_remaining();
+    let capacity = ship.cargo.capacity as u64;
+    if units == 0 || capacity == 0 {
+        return Ok(None);
+    }
+    let Some(source) = find_source(context, ship, &delivery.trade_symbol).await? else {
+        return Ok(None);
+    };
+
+    let navigator = &context.navigator;
+    let specs = ShipSpecs::from_ship(ship);
+    let full_tank = ShipSpecs {
+        current_fuel: specs.fuel_capacity,
+        ..specs
+    };
+    let route = |from: &str, to: &str, specs: &ShipSpecs| -> Option<Route> {
+        navigator
+            .planner()
+            .plan(from, to, specs, navigator.objective())
+    };
+    let (Some(outbound), Some(delivering), Some(returning)) = (
+        route(from, source.waypoint(), &specs),
+        route(source.waypoint(), &delivery.destination_symbol, &full_tank),
+        route(&delivery.destination_symbol, source.waypoint(), &full_tank),
+    ) else {
+        return Ok(None);
+    };
+
+    let trips = units.div_ceil(capacity);
+    let mut seconds = outbound.total_seconds
+        + trips * delivering.total_seconds
+        + (trips - 1) * returning.total_seconds;
+    let fuel = outbound.total_fuel as u64
+        + trips * delivering.total_fuel as u64
+        + (trips - 1) * returning.total_fuel as u64;
+    if let Source::Mine { .. } = source {
+        seconds += units * MINING_SECONDS_PER_UNIT;
+    }
+    Ok(Some(DeliveryPlan {
+        trade_symbol: delivery.trade_symbol.clone(),
+        units,
+        source,
+        destination: delivery.destination_symbol.clone(),
+        trips,
+        seconds,
+        fuel,
+    }))
+}
+
+/// Plans every outstanding delivery of `contract` for `ship`, one after the
+/// other, or `None` if any of them can't be done.
+pub async fn plan_contract(
+    context: &FleetContext,
+    ship: &Ship,
+    contract: &Contract,
+) -> Result<Option<Vec<DeliveryPlan>>> {
+    let mut plans = Vec::new();
+    let mut position = ship.nav.waypoint_symbol.clone();
+    for delivery in &contract.terms.deliveries {
+        if delivery.units_remaining() == 0 {
+            continue;
+        }
+        let Some(plan) = plan_delivery(context, ship, &position, delivery).await? else {
+            return Ok(None);
+        };
+        position = plan.destination.clone();
+        plans.push(plan);
+    }
+    Ok(Some(plans))
+}
+
+pub async fn active_contract(context: &FleetContext) -> Result<Option<Contract>> {
+    Ok(context
+        .client()
+        .get_contracts()
+        .await?
+        .into_iter()
+        .find(is_active))
+}
+
+/// Not yet accepted and still open for acceptance at `now`.
+pub fn is_open_offer(contract: &Contract, now: DateTime<Utc>) -> bool {
+    !contract.accepted && contract.deadline_to_accept > now
+}
+
+/// Gets a new contract when none is active: takes the first open offer the
+/// evaluator accepts or, if there are no open offers, flies `negotiator` to
+/// the agent's headquarters to negotiate one and puts that through the
+/// evaluator. Returns the accepted contract, if any.
+pub async fn obtain_contract(
+    context: &FleetContext,
+    negotiator: &mut Ship,
+) -> Result<Option<Contract>> {
+    let client = context.client();
+    let ships = client.fetch_all::<Ship>("/my/ships").await?;
+    let now = client.clock().utc_now();
+    let offers: Vec<Contract> = client
+        .get_contracts()
+        .await?
+        .into_iter()
+        .filter(|contract| is_open_offer(contract, now))
+        .collect();
+
+    let offers = if offers.is_empty() {
+        let headquarters = client.get_agent_data().await?.headquarters;
+        println!(
+            "{} negotiating a new contract at {}",
+            negotiator.symbol, headquarters
+        );
+        context.navigator.go_to(negotiator, &headquarters).await?;
+        context.navigator.ensure_docked(negotiator).await?;
+        vec![client.negotiate_contract(&negotiator.symbol).await?]
+    } else {
+        offers
+    };
+
+    for offer in offers {
+        println!("Offer {}", offer);
+        if context
+            .evaluator
+            .accept_if_profitable(context, &ships, &offer)
+            .await?
+        {
+            return Ok(Some(offer));
+        }
+    }
+    Ok(None)
+}
+
+/// Fills the hold with up to `target` units of `symbol` from `source`.
+/// Returns false if mining gave up before the hold had them all.
+async fn source_goods(
+    context: &FleetContext,
+    ship: &mut Ship,
+    source: &Source,
+    symbol: &TradeSymbol,
+    target: u32,
+) -> Result<bool> {
+    let navigator = &context.navigator;
+    let client = context.client();
+
+    // Make room by dumping anything that isn't for the contract.
+    let space = ship.cargo.capacity.saturating_sub(ship.cargo.units);
+    if space < target.saturating_sub(units_held(ship, symbol)) {
+        let others: Vec<(TradeSymbol, u32)> = ship
+            .cargo
+            .inventory
+            .iter()
+            .filter(|cargo| &cargo.symbol != symbol)
+            .map(|cargo| (cargo.symbol.clone(), cargo.units))
+            .collect();
+        for (other, units) in others {
+            ship.cargo = client.jettison_cargo(&ship.symbol, &other, units).await?;
+            println!("{} jettisoned {} {}", ship.symbol, units, other);
+        }
+    }
+
+    navigator.go_to(ship, source.waypoint()).await?;
+    match source {
+        Source::Buy { .. } => {
+            navigator.ensure_docked(ship).await?;
+            let market = record_market(context, ship).await?;
+            let trade_volume = market
+                .trade_good(symbol)
+                .map_or(target, |good| good.trade_volume.max(1));
+            while units_held(ship, symbol) < target {
+                let units = (target - units_held(ship, symbol)).min(trade_volume);
+                let purchase = client.purchase_goods(&ship.symbol, symbol, units).await?;
+                context
+                    .market_store
+                    .lock()
+                    .unwrap()
+                    .record_transaction(&purchase.transaction)?;
+                println!(
+                    "{} bought {} {} for {}",
+                    ship.symbol, units, symbol, purchase.transaction.total_price
+                );
+                ship.cargo = purchase.cargo;
+            }
+        }
+        Source::Mine { asteroid } => {
+            let targets = [symbol.clone()];
+            let mut extractions = 0;
+            while units_held(ship, symbol) < target {
+                if extractions >= target * MAX_EXTRACTIONS_PER_UNIT {
+                    println!(
+                        "{} gave up mining {} at {} after {} extractions",
+                        ship.symbol, symbol, asteroid, extractions
+                    );
+                    return Ok(false);
+                }
+                if extract_once(context, ship, &targets).await?.is_none() {
+                    continue;
+                }
+                extractions += 1;
+                if ship.cargo.units < ship.cargo.capacity {
+                    continue;
+                }
+                let others: Vec<(TradeSymbol, u32)> = ship
+                    .cargo
+                    .inventory
+                    .iter()
+                    .filter(|cargo| &cargo.symbol != symbol)
+                    .map(|cargo| (cargo.symbol.clone(), cargo.units))
+                    .collect();
+                for (other, units) in others {
+                    ship.cargo = client.jettison_cargo(&ship.symbol, &other, units).await?;
+                }
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Works the active contract: sources each delivery's goods by buying or
+/// mining, carries them to the destination a hold at a time and fulfils the
+/// contract once everything is delivered. Without an active contract it
+/// tries to obtain one, and returns if that fails.
+pub async fn run(context: FleetContext, mut ship: Ship) -> Result<()> {
+    let navigator = &context.navigator;
+    let client = context.client();
+    loop {
+        let contract = match active_contract(&context).await? {
+            Some(contract) => contract,
+            None => match obtain_contract(&context, &mut ship).await? {
+                Some(contract) => contract,
+                None => {
+                    println!("{} has no active contract to work", ship.symbol);
+                    return Ok(());
+                }
+            },
+        };
+        let Some(delivery) = contract
+            .terms
+            .deliveries
+            .iter()
+            .find(|delivery| delivery.units_remaining() > 0)
+        else {
+            let result = client.fulfill_contract(&contract.id).await?;
+            println!(
+                "Fulfilled contract {} for {} credits, now at {}",
+                contract.id, contract.terms.payment.payment_on_fulfilled, result.agent.credits
+            );
+            continue;
+        };
+
+        let symbol = &delivery.trade_symbol;
+        let target = delivery.units_remaining().min(ship.cargo.capacity as u64) as u32;
+        if units_held(&ship, symbol) < target {
+            let Some(source) = find_source(&context, &ship, symbol).await? else {
+                println!("{} has no source for {}", ship.symbol, symbol);
+                return Ok(());
+            };
+            println!(
+                "{} sourcing {} {} from {}",
+                ship.symbol,
+                target,
+                symbol,
+                source.waypoint()
+            );
+            if !source_goods(&context, &mut ship, &source, symbol, target).await? {
+                return Ok(());
+            }
+        }
+
+        navigator
+            .go_to(&mut ship, &delivery.destination_symbol)
+            .await?;
+        navigator.ensure_docked(&mut ship).await?;
+        let units = units_held(&ship, symbol).min(delivery.units_remaining() as u32);
+        let result = client
+            .deliver_contract(&contract.id, &ship.symbol, symbol, units)
+            .await?;
+        ship.cargo = result.cargo;
+        if let Some(delivered) = result
+            .contract
+            .terms
+            .deliveries
+            .iter()
+            .find(|delivered| &delivered.trade_symbol == symbol)
+        {
+            println!(
+                "{} delivered {} {} ({}/{})",
+                ship.symbol, units, symbol, delivered.units_fulfilled, delivered.units_required
+            );
+        }
+    }
+}
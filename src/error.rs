@@ -0,0 +1,341 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+pub type Result<T> = std::result::Result<T, SpaceTradersError>;
+
+/// The `{"error": {...}}` envelope the API returns for every failed request.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ErrorResponse {
+    pub error: ApiError,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiError {
+    pub code: u32,
+    pub message: String,
+    #[serde(default)]
+    pub data: Option<serde_json::Value>,
+}
+
+pub const RATE_LIMIT_ERROR: u32 = 429;
+pub const COOLDOWN_CONFLICT_ERROR: u32 = 4000;
+pub const TOKEN_ERRORS: std::ops::RangeInclusive<u32> = 4100..=4108;
+pub const NAVIGATE_IN_TRANSIT_ERROR: u32 = 4200;
+pub const NAVIGATE_INSUFFICIENT_FUEL_ERROR: u32 = 4203;
+pub const SHIP_IN_TRANSIT_ERROR: u32 = 4214;
+pub const PURCHASE_SHIP_CREDITS_ERROR: u32 = 4216;
+pub const SHIP_SURVEY_VERIFICATION_ERROR: u32 = 4220;
+pub const SHIP_SURVEY_EXPIRATION_ERROR: u32 = 4221;
+pub const SHIP_SURVEY_EXHAUSTED_ERROR: u32 = 4224;
+pub const SHIP_NOT_IN_ORBIT_ERROR: u32 = 4236;
+pub const SHIP_NOT_DOCKED_ERROR: u32 = 4244;
+pub const MARKET_TRADE_INSUFFICIENT_CREDITS_ERROR: u32 = 4600;
+
+#[derive(Debug)]
+pub enum SpaceTradersError {
+    /// The ship's reactor is still cooling down from its last action.
+    Cooldown {
+        remaining_seconds: u64,
+    },
+    InsufficientFuel {
+        fuel_required: Option<u64>,
+        fuel_available: Option<u64>,
+    },
+    InTransit {
+        seconds_to_arrival: Option<u64>,
+    },
+    NotDocked,
+    NotInOrbit,
+    /// The survey sent with an extraction is expired, exhausted or unknown.
+    SurveyUnusable {
+        message: String,
+    },
+    InsufficientCredits {
+        message: String,
+    },
+    RateLimited {
+        retry_after: Option<f64>,
+    },
+    TokenInvalid {
+        message: String,
+    },
+    /// Any other error the API reported with a code we don't handle specifically.
+    Api {
+        status: u16,
+        code: u32,
+        message: String,
+        data: Option<serde_json::Value>,
+    },
+    /// The route planner found no way to reach `to` on the fuel available.
+    NoRoute {
+        from: String,
+        to: String,
+    },
+    /// A non-success status whose body wasn't a SpaceTraders error envelope.
+    UnexpectedStatus {
+        status: u16,
+        body: String,
+    },
+    /// The configuration is unreadable or invalid.
+    Config {
+        message: String,
+    },
+    /// A replayed request had no recorded response to serve.
+    Fixture {
+        message: String,
+    },
+    /// A command-line request that can't be carried out as given.
+    Command {
+        message: String,
+    },
+    Http(reqwest::Error),
+    Decode(serde_json::Error),
+    Io(std::io::Error),
+}
+
+impl SpaceTradersError {
+    pub fn from_api(status: u16, error: ApiError) -> Self {
+        let data = error.data.as_ref();
+        let number = |field: &str| data.and_then(|data| data[field].as_u64());
+
+        match error.code {
+            COOLDOWN_CONFLICT_ERROR => SpaceTradersError::Cooldown {
+                remaining_seconds: data
+                    .and_then(|data| data["cooldown"]["remainingSeconds"].as_u64())
+                    .unwrap_or(0),
+            },
+            NAVIGATE_INSUFFICIENT_FUEL_ERROR => SpaceTradersError::InsufficientFuel {
+                fuel_required: number("fuelRequired"),
+                fuel_available: number("fuelAvailable"),
+            },
+            NAVIGATE_IN_TRANSIT_ERROR | SHIP_IN_TRANSIT_ERROR => SpaceTradersError::InTransit {
+                seconds_to_arrival: number("secondsToArrival"),
+            },
+            SHIP_NOT_DOCKED_ERROR => SpaceTradersError::NotDocked,
+            SHIP_NOT_IN_ORBIT_ERROR => SpaceTradersError::NotInOrbit,
+            SHIP_SURVEY_VERIFICATION_ERROR
+            | SHIP_SURVEY_EXPIRATION_ERROR
+            | SHIP_SURVEY_EXHAUSTED_ERROR => SpaceTradersError::SurveyUnusable {
+                message: error.message,
+            },
+            PURCHASE_SHIP_CREDITS_ERROR | MARKET_TRADE_INSUFFICIENT_CREDITS_ERROR => {
+                SpaceTradersError::InsufficientCredits {
+                    message: error.message,
+                }
+            }
+            RATE_LIMIT_ERROR => SpaceTradersError::RateLimited {
+                retry_after: data.and_then(|data| data["retryAfter"].as_f64()),
+            },
+            code if TOKEN_ERRORS.contains(&code) || status == 401 => {
+                SpaceTradersError::TokenInvalid {
+                    message: error.message,
+                }
+            }
+            code => SpaceTradersError::Api {
+                status,
+                code,
+                message: error.message,
+                data: error.data,
+            },
+        }
+    }
+}
+
+impl fmt::Display for SpaceTradersError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpaceTradersError::Cooldown { remaining_seconds } => {
+                write!(f, "ship is on cooldown for {}s", remaining_seconds)
+            }
+            SpaceTradersError::InsufficientFuel {
+                fuel_required,
+                fuel_available,
+            } => write!(
+                f,
+                "insufficient fuel (required: {:?}, available: {:?})",
+                fuel_required, fuel_available
+            ),
+            SpaceTradersError::InTransit { seconds_to_arrival } => {
+                write!(
+                    f,
+                    "ship is in transit (arrives in {:?}s)",
+                    seconds_to_arrival
+                )
+            }
+            SpaceTradersError::NotDocked => write!(f, "ship is not docked"),
+            SpaceTradersError::NotInOrbit => write!(f, "ship is not in orbit"),
+            SpaceTradersError::SurveyUnusable { message } => {
+                write!(f, "survey can't be used: {}", message)
+            }
+            SpaceTradersError::InsufficientCredits { message } => {
+                write!(f, "insufficient credits: {}", message)
+            }
+            SpaceTradersError::RateLimited { retry_after } => {
+                write!(f, "rate limited (retry after {:?}s)", retry_after)
+            }
+            SpaceTradersError::TokenInvalid { message } => write!(f, "invalid token: {}", message),
+            SpaceTradersError::Api {
+                status,
+                code,
+                message,
+                ..
+            } => write!(f, "API error {} (HTTP {}): {}", code, status, message),
+            SpaceTradersError::NoRoute { from, to } => {
+                write!(f, "no route from {} to {}", from, to)
+            }
+            SpaceTradersError::UnexpectedStatus { status, body } => {
+                write!(f, "unexpected HTTP {}: {}", status, body)
+            }
+            SpaceTradersError::Config { message } => write!(f, "invalid config: {}", message),
+            SpaceTradersError::Fixture { message } => write!(f, "fixture error: {}", message),
+            SpaceTradersError::Command { message } => f.write_str(message),
+            SpaceTradersError::Http(err) => write!(f, "HTTP error: {}", err),
+            SpaceTradersError::Decode(err) => write!(f, "failed to decode response: {}", err),
+            SpaceTradersError::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SpaceTradersError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SpaceTradersError::Http(err) => Some(err),
+            SpaceTradersError::Decode(err) => Some(err),
+            SpaceTradersError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for SpaceTradersError {
+    fn from(err: reqwest::Error) -> Self {
+        SpaceTradersError::Http(err)
+    }
+}
+
+impl From<serde_json::Error> for SpaceTradersError {
+    fn from(err: serde_json::Error) -> Self {
+        SpaceTradersError::Decode(err)
+    }
+}
+
+impl From<std::io::Error> for SpaceTradersError {
+    fn from(err: std::io::Error) -> Self {
+        SpaceTradersError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    /// Decodes `error` from the envelope the API would send with `status`.
+    fn from_envelope(status: u16, error: Value) -> SpaceTradersError {
+        let envelope: ErrorResponse = serde_json::from_value(json!({ "error": error })).unwrap();
+        SpaceTradersError::from_api(status, envelope.error)
+    }
+
+    #[test]
+    fn cooldown_carries_the_remaining_seconds() {
+        let err = from_envelope(
+            409,
+            json!({
+                "code": 4000,
+                "message": "Ship action is still on cooldown for 42 second(s).",
+                "data": { "cooldown": { "shipSymbol": "SHIP-1", "remainingSeconds": 42 } },
+            }),
+        );
+
+        assert!(matches!(
+            err,
+            SpaceTradersError::Cooldown {
+                remaining_seconds: 42
+            }
+        ));
+    }
+
+    #[test]
+    fn insufficient_fuel_carries_required_and_available() {
+        let err = from_envelope(
+            400,
+            json!({
+                "code": 4203,
+                "message": "Navigate request failed. Ship requires more fuel.",
+                "data": { "shipSymbol": "SHIP-1", "fuelRequired": 120, "fuelAvailable": 37 },
+            }),
+        );
+
+        assert!(matches!(
+            err,
+            SpaceTradersError::InsufficientFuel {
+                fuel_required: Some(120),
+                fuel_available: Some(37),
+            }
+        ));
+    }
+
+    #[test]
+    fn rate_limit_carries_retry_after() {
+        let err = from_envelope(
+            429,
+            json!({
+                "code": 429,
+                "message": "You have reached your API limit.",
+                "data": { "type": "IP-based limits", "retryAfter": 1.5, "limitBurst": 30 },
+            }),
+        );
+
+        assert!(matches!(
+            err,
+            SpaceTradersError::RateLimited {
+                retry_after: Some(wait)
+            } if wait == 1.5
+        ));
+    }
+
+    #[test]
+    fn token_errors_and_unauthorized_responses_are_invalid_tokens() {
+        let token = from_envelope(
+            401,
+            json!({ "code": 4104, "message": "Token version does not match the server." }),
+        );
+        let unauthorized = from_envelope(401, json!({ "code": 401, "message": "Unauthorized" }));
+
+        assert!(
+            matches!(&token, SpaceTradersError::TokenInvalid { message } if message.contains("Token version"))
+        );
+        assert!(matches!(
+            unauthorized,
+            SpaceTradersError::TokenInvalid { .. }
+        ));
+    }
+
+    #[test]
+    fn unknown_codes_fall_through_to_api() {
+        let err = from_envelope(
+            400,
+            json!({
+                "code": 4999,
+                "message": "Something new went wrong.",
+                "data": { "detail": "unmapped" },
+            }),
+        );
+
+        match err {
+            SpaceTradersError::Api {
+                status,
+                code,
+                message,
+                data,
+            } => {
+                assert_eq!((status, code), (400, 4999));
+                assert_eq!(message, "Something new went wrong.");
+                assert_eq!(data, Some(json!({ "detail": "unmapped" })));
+            }
+            other => panic!("expected an Api error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,394 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use chrono::{DateTime, Utc};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Method, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{Result, SpaceTradersError};
+use crate::rate_limit::{RATE_LIMIT_REMAINING, RATE_LIMIT_RESET, RETRY_AFTER};
+
+/// What secrets are replaced with in fixture files.
+pub const REDACTED: &str = "<redacted>";
+
+/// Response headers worth keeping: the ones the client acts on.
+const KEPT_HEADERS: [&str; 3] = [RETRY_AFTER, RATE_LIMIT_REMAINING, RATE_LIMIT_RESET];
+
+/// One request and the response the server gave it, as stored in a
+/// fixture file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Exchange {
+    pub method: String,
+    /// Path below the base URL, query string included.
+    pub path: String,
+    /// Whether the request carried a bearer token; the token itself is
+    /// never written.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorization: Option<String>,
+    #[serde(
+        rename = "requestBody",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub request_body: Option<Value>,
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// The response body, if it was JSON. Empty bodies are stored as neither.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<Value>,
+    /// The response body, if it wasn't JSON.
+    #[serde(
+        rename = "responseText",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub response_text: Option<String>,
+    #[serde(rename = "recordedAt")]
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl Exchange {
+    /// Captures a request/response pair with `token` scrubbed from both
+    /// bodies, along with any `token` field (as in `/register` replies).
+    #[allow(clippy::too_many_arguments)]
+    pub fn capture(
+        method: &Method,
+        path: &str,
+        token: Option<&str>,
+        request_body: Option<&Value>,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &[u8],
+        recorded_at: DateTime<Utc>,
+    ) -> Self {
+        let mut request_body = request_body.cloned();
+        if let Some(body) = &mut request_body {
+            redact(body, token);
+        }
+        let (response, response_text) = if body.is_empty() {
+            (None, None)
+        } else {
+            match serde_json::from_slice::<Value>(body) {
+                Ok(mut value) => {
+                    redact(&mut value, token);
+                    (Some(value), None)
+                }
+                Err(_) => (None, Some(String::from_utf8_lossy(body).into_owned())),
+            }
+        };
+        Exchange {
+            method: method.to_string(),
+            path: path.to_string(),
+            authorization: token.map(|_| format!("Bearer {}", REDACTED)),
+            request_body,
+            status: status.as_u16(),
+            headers: KEPT_HEADERS
+                .iter()
+                .filter_map(|name| {
+                    let value = headers.get(*name)?.to_str().ok()?;
+                    Some((name.to_string(), value.to_string()))
+                })
+                .collect(),
+            response,
+            response_text,
+            recorded_at,
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    pub fn header_map(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                headers.insert(name, value);
+            }
+        }
+        headers
+    }
+
+    /// The response body as the server sent it, give or take whitespace.
+    pub fn body(&self) -> Vec<u8> {
+        match (&self.response, &self.response_text) {
+            (Some(value), _) => value.to_string().into_bytes(),
+            (None, Some(text)) => text.clone().into_bytes(),
+            (None, None) => Vec::new(),
+        }
+    }
+
+    fn file_name(&self, index: usize) -> String {
+        let path = self.path.split('?').next().unwrap_or_default();
+        let slug: String = path
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        let slug: String = slug.trim_matches('-').chars().take(60).collect();
+        format!("{:04}-{}-{}.json", index, self.method, slug)
+    }
+}
+
+/// Replaces every occurrence of `token`, and every value of a `token` field,
+/// with `REDACTED`.
+fn redact(value: &mut Value, token: Option<&str>) {
+    match value {
+        Value::String(text) => {
+            if let Some(token) = token.filter(|token| !token.is_empty()) {
+                if text.contains(token) {
+                    *text = text.replace(token, REDACTED);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| redact(item, token)),
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                if key == "token" && field.is_string() {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact(field, token);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Fixture files in `dir`, in recording order.
+fn fixture_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// The number a fixture file name starts with, if it has one.
+fn fixture_index(path: &Path) -> Option<usize> {
+    let name = path.file_name()?.to_str()?;
+    name.split('-').next()?.parse().ok()
+}
+
+/// Writes each exchange the client makes to its own numbered JSON file, so a
+/// session captured once can be replayed by `Replay`.
+#[derive(Debug)]
+pub struct Recorder {
+    dir: PathBuf,
+    next: Mutex<usize>,
+}
+
+impl Recorder {
+    /// Records into `dir`, creating it if needed. Fixtures already there are
+    /// kept and new ones numbered after them.
+    pub fn create(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let last = fixture_files(&dir)?
+            .iter()
+            .filter_map(|path| fixture_index(path))
+            .max()
+            .unwrap_or(0);
+        let next = last + 1;
+        Ok(Recorder {
+            dir,
+            next: Mutex::new(next),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn record(&self, exchange: &Exchange) -> Result<()> {
+        let index = {
+            let mut next = self.next.lock().unwrap();
+            *next += 1;
+            *next - 1
+        };
+        let path = self.dir.join(exchange.file_name(index));
+        fs::write(path, serde_json::to_string_pretty(exchange)?)?;
+        Ok(())
+    }
+}
+
+/// Serves recorded exchanges back instead of going to the network.
+///
+/// Each request gets the earliest unserved exchange with the same method and
+/// path, so requests come back in recording order even when concurrent ships
+/// interleave them differently this time. A request with nothing left to
+/// serve fails with `SpaceTradersError::Fixture`.
+#[derive(Debug)]
+pub struct Replay {
+    dir: PathBuf,
+    exchanges: Mutex<VecDeque<Exchange>>,
+}
+
+impl Replay {
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        let exchanges = fixture_files(&dir)?
+            .into_iter()
+            .map(|path| {
+                let exchange: Exchange = serde_json::from_slice(&fs::read(&path)?)?;
+                Ok(exchange)
+            })
+            .collect::<Result<VecDeque<_>>>()?;
+        Ok(Replay {
+            dir,
+            exchanges: Mutex::new(exchanges),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// When the first exchange was recorded; a fake clock starting here sees
+    /// the recorded arrival and cooldown times as the server meant them.
+    pub fn started_at(&self) -> Option<DateTime<Utc>> {
+        self.exchanges
+            .lock()
+            .unwrap()
+            .front()
+            .map(|exchange| exchange.recorded_at)
+    }
+
+    /// How many exchanges haven't been served yet.
+    pub fn remaining(&self) -> usize {
+        self.exchanges.lock().unwrap().len()
+    }
+
+    pub fn next(&self, method: &Method, path: &str) -> Result<Exchange> {
+        let mut exchanges = self.exchanges.lock().unwrap();
+        let index = exchanges
+            .iter()
+            .position(|exchange| exchange.method == method.as_str() && exchange.path == path)
+            .ok_or_else(|| SpaceTradersError::Fixture {
+                message: format!(
+                    "no recorded response left for {} {} in {}",
+                    method,
+                    path,
+                    self.dir.display()
+                ),
+            })?;
+        Ok(exchanges.remove(index).unwrap())
+    }
+}
+
+/// Where a client's exchanges go besides the network, if anywhere.
+#[derive(Debug)]
+pub enum Fixtures {
+    Record(Recorder),
+    Replay(Replay),
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const TOKEN: &str = "secret-token";
+
+    fn capture(request_body: Option<&Value>, body: &[u8]) -> Exchange {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("2"));
+        headers.insert("x-unrelated", HeaderValue::from_static("dropped"));
+        Exchange::capture(
+            &Method::POST,
+            "/my/ships/SHIP-1/extract?attempt=2",
+            Some(TOKEN),
+            request_body,
+            StatusCode::CREATED,
+            &headers,
+            body,
+            Utc::now(),
+        )
+    }
+
+    #[test]
+    fn capture_redacts_the_token_everywhere() {
+        let request = json!({ "note": format!("Bearer {}", TOKEN) });
+        let response = json!({ "data": { "token": "another-token", "echo": [TOKEN] } });
+        let exchange = capture(Some(&request), response.to_string().as_bytes());
+
+        let stored = serde_json::to_string(&exchange).unwrap();
+        assert!(!stored.contains(TOKEN));
+        assert!(!stored.contains("another-token"));
+        assert_eq!(exchange.authorization, Some(format!("Bearer {}", REDACTED)));
+        assert_eq!(exchange.response.unwrap()["data"]["token"], REDACTED);
+    }
+
+    #[test]
+    fn capture_keeps_only_rate_limit_headers() {
+        let exchange = capture(None, b"");
+
+        assert_eq!(exchange.headers.len(), 1);
+        assert_eq!(exchange.header_map()[RETRY_AFTER], "2");
+        assert_eq!(exchange.status(), StatusCode::CREATED);
+        assert!(exchange.body().is_empty());
+    }
+
+    #[test]
+    fn non_json_bodies_are_kept_as_text() {
+        let exchange = capture(None, b"Bad Gateway");
+
+        assert_eq!(exchange.response, None);
+        assert_eq!(exchange.body(), b"Bad Gateway");
+    }
+
+    #[test]
+    fn file_names_sort_in_recording_order() {
+        let exchange = capture(None, b"");
+
+        assert_eq!(
+            exchange.file_name(12),
+            "0012-POST-my-ships-SHIP-1-extract.json"
+        );
+    }
+
+    #[test]
+    fn recording_numbers_after_the_highest_existing_fixture() {
+        let dir =
+            std::env::temp_dir().join(format!("spacetraders-recorder-gaps-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("0001-GET-my-agent.json"), "first").unwrap();
+        fs::write(dir.join("0003-GET-my-agent.json"), "third").unwrap();
+
+        let recorder = Recorder::create(&dir).unwrap();
+        recorder.record(&capture(None, b"")).unwrap();
+
+        let names: Vec<String> = fixture_files(&dir)
+            .unwrap()
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "0001-GET-my-agent.json",
+                "0003-GET-my-agent.json",
+                "0004-POST-my-ships-SHIP-1-extract.json",
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("0003-GET-my-agent.json")).unwrap(),
+            "third"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
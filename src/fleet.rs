@@ -0,0 +1,431 @@
+use std::{collections::HashMap, fmt, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+use crate::client::ApiClient;
+use crate::contract_evaluator::ContractEvaluator;
+use crate::error::Result;
+use crate::market_store::SharedMarketStore;
+use crate::mining::MiningConfig;
+use crate::models::{Contract, Ship, ShipRole, ShipType};
+use crate::navigation::Navigator;
+use crate::shipyard::PurchasePlanner;
+use crate::survey_cache::SharedSurveyCache;
+use crate::{contracts, mining, surveying, trading};
+
+/// What a ship spends its time doing.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Behaviour {
+    Miner,
+    Trader,
+    Surveyor,
+    ContractRunner,
+    Idle,
+}
+
+impl fmt::Display for Behaviour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Behaviour::Miner => "miner",
+            Behaviour::Trader => "trader",
+            Behaviour::Surveyor => "surveyor",
+            Behaviour::ContractRunner => "contract runner",
+            Behaviour::Idle => "idle",
+        })
+    }
+}
+
+/// How ships are mapped to behaviours. A per-ship assignment wins over the
+/// ship's role; ships matching neither get `default_behaviour`.
+#[derive(Debug, Clone)]
+pub struct FleetConfig {
+    pub ships: HashMap<String, Behaviour>,
+    pub roles: HashMap<ShipRole, Behaviour>,
+    pub default_behaviour: Behaviour,
+    /// What contract runners do while there is no contract work: nothing
+    /// active and an offer already on the table that wasn't worth accepting.
+    pub without_contract: Behaviour,
+    /// How often the controller re-reads the fleet and contracts.
+    pub refresh_interval: Duration,
+    /// Ships to buy, in order of priority.
+    pub purchases: Vec<PurchaseTarget>,
+}
+
+/// Keep buying `ship_type` until `count` ships do `behaviour`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PurchaseTarget {
+    pub ship_type: ShipType,
+    pub behaviour: Behaviour,
+    pub count: usize,
+}
+
+impl Default for FleetConfig {
+    fn default() -> Self {
+        FleetConfig {
+            ships: HashMap::new(),
+            roles: HashMap::from([
+                (ShipRole::Excavator, Behaviour::Miner),
+                (ShipRole::Command, Behaviour::ContractRunner),
+                (ShipRole::Hauler, Behaviour::Trader),
+                (ShipRole::Transport, Behaviour::Trader),
+                (ShipRole::Surveyor, Behaviour::Surveyor),
+            ]),
+            default_behaviour: Behaviour::Idle,
+            without_contract: Behaviour::Miner,
+            refresh_interval: Duration::from_secs(60),
+            purchases: vec![PurchaseTarget {
+                ship_type: ShipType::ShipMiningDrone,
+                behaviour: Behaviour::Miner,
+                count: 3,
+            }],
+        }
+    }
+}
+
+impl FleetConfig {
+    pub fn behaviour_for(&self, ship: &Ship, has_contract_work: bool) -> Behaviour {
+        let behaviour = self
+            .ships
+            .get(&ship.symbol)
+            .or_else(|| self.roles.get(&ship.registration.role))
+            .copied()
+            .unwrap_or(self.default_behaviour);
+        match behaviour {
+            Behaviour::ContractRunner if !has_contract_work => self.without_contract,
+            behaviour => behaviour,
+        }
+    }
+}
+
+/// Everything a ship task needs. Cloning is cheap and every clone shares the
+/// same client, rate limiter, scheduler, market store and survey cache.
+#[derive(Debug, Clone)]
+pub struct FleetContext {
+    pub navigator: Navigator,
+    pub market_store: SharedMarketStore,
+    pub home_system: String,
+    pub mining: MiningConfig,
+    pub surveys: SharedSurveyCache,
+    pub evaluator: ContractEvaluator,
+    /// Credits ship tasks never spend on goods.
+    pub credit_reserve: u64,
+}
+
+impl FleetContext {
+    pub fn client(&self) -> &ApiClient {
+        self.navigator.client()
+    }
+}
+
+#[derive(Debug)]
+struct ShipTask {
+    behaviour: Behaviour,
+    handle: Option<JoinHandle<Result<()>>>,
+}
+
+/// Runs one task per ship and keeps each ship's behaviour in line with the
+/// config as ships are bought and contracts come and go.
+#[derive(Debug)]
+pub struct FleetController {
+    context: FleetContext,
+    config: FleetConfig,
+    tasks: HashMap<String, ShipTask>,
+    has_contract_work: bool,
+    purchases: Option<PurchasePlanner>,
+}
+
+impl FleetController {
+    pub fn new(context: FleetContext, config: FleetConfig) -> Self {
+        FleetController {
+            context,
+            config,
+            tasks: HashMap::new(),
+            has_contract_work: false,
+            purchases: None,
+        }
+    }
+
+    /// Buys the ships `config.purchases` asks for after every refresh.
+    pub fn with_purchase_planner(mut self, planner: PurchasePlanner) -> Self {
+        self.purchases = Some(planner);
+        self
+    }
+
+    pub fn context(&self) -> &FleetContext {
+        &self.context
+    }
+
+    pub fn config(&self) -> &FleetConfig {
+        &self.config
+    }
+
+    /// The behaviour currently assigned to each ship.
+    pub fn assignments(&self) -> impl Iterator<Item = (&str, Behaviour)> {
+        self.tasks
+            .iter()
+            .map(|(symbol, task)| (symbol.as_str(), task.behaviour))
+    }
+
+    /// How many ships are assigned `behaviour`.
+    pub fn count(&self, behaviour: Behaviour) -> usize {
+        self.tasks
+            .values()
+            .filter(|task| task.behaviour == behaviour)
+            .count()
+    }
+
+    /// The first purchase target the fleet hasn't reached yet.
+    pub fn needed_purchase(&self) -> Option<&PurchaseTarget> {
+        self.config
+            .purchases
+            .iter()
+            .find(|target| self.count(target.behaviour) < target.count)
+    }
+
+    /// Starts a newly bought ship doing `behaviour`.
+    pub fn register_purchase(&mut self, ship: Ship, behaviour: Behaviour) {
+        self.config.ships.insert(ship.symbol.clone(), behaviour);
+        self.register_ship(ship, self.has_contract_work);
+    }
+
+    /// Starts (or restarts) `ship` with the behaviour the config gives it,
+    /// e.g. right after buying it.
+    pub fn register_ship(&mut self, ship: Ship, has_contract_work: bool) {
+        let behaviour = self.config.behaviour_for(&ship, has_contract_work);
+        let unchanged = self.tasks.get(&ship.symbol).is_some_and(|task| {
+            task.behaviour == behaviour
+                && !task
+                    .handle
+                    .as_ref()
+                    .is_some_and(|handle| handle.is_finished())
+        });
+        if unchanged {
+            return;
+        }
+        if let Some(task) = self.tasks.remove(&ship.symbol) {
+            if let Some(handle) = task.handle {
+                handle.abort();
+            }
+        }
+        println!("{} assigned as {}", ship.symbol, behaviour);
+        let symbol = ship.symbol.clone();
+        let handle = self.spawn(behaviour, ship);
+        self.tasks.insert(symbol, ShipTask { behaviour, handle });
+    }
+
+    fn spawn(&self, behaviour: Behaviour, ship: Ship) -> Option<JoinHandle<Result<()>>> {
+        self.context.navigator.scheduler().observe_ship(&ship);
+        let context = self.context.clone();
+        match behaviour {
+            Behaviour::Miner => Some(tokio::spawn(mining::run(context, ship))),
+            Behaviour::Trader => Some(tokio::spawn(trading::run(context, ship))),
+            Behaviour::Surveyor => Some(tokio::spawn(surveying::run(context, ship))),
+            Behaviour::ContractRunner => Some(tokio::spawn(contracts::run(context, ship))),
+            Behaviour::Idle => None,
+        }
+    }
+
+    /// Logs tasks that have stopped so the next refresh restarts them.
+    async fn reap(&mut self) {
+        for (symbol, task) in &mut self.tasks {
+            if !task
+                .handle
+                .as_ref()
+                .is_some_and(|handle| handle.is_finished())
+            {
+                continue;
+            }
+            match task.handle.take().unwrap().await {
+                Ok(Ok(())) => println!("{} finished its {} task", symbol, task.behaviour),
+                Ok(Err(err)) => println!("{} stopped: {}", symbol, err),
+                Err(err) => println!("{} task failed: {}", symbol, err),
+            }
+            task.behaviour = Behaviour::Idle;
+        }
+    }
+
+    /// Re-reads the fleet and contracts, starting tasks for new ships and
+    /// reassigning any ship whose behaviour should change.
+    pub async fn refresh(&mut self) -> Result<()> {
+        self.reap().await;
+        let client = self.context.client().clone();
+        let ships = client.fetch_all::<Ship>("/my/ships").await?;
+        self.has_contract_work =
+            has_contract_work(&client.get_contracts().await?, client.clock().utc_now());
+
+        let symbols: Vec<String> = ships.iter().map(|ship| ship.symbol.clone()).collect();
+        self.tasks.retain(|symbol, task| {
+            let keep = symbols.contains(symbol);
+            if !keep {
+                if let Some(handle) = &task.handle {
+                    handle.abort();
+                }
+            }
+            keep
+        });
+        for ship in ships {
+            self.register_ship(ship, self.has_contract_work);
+        }
+        Ok(())
+    }
+
+    /// Refreshes the fleet, and buys ships if a planner is set, forever.
+    pub async fn run(&mut self) -> Result<()> {
+        loop {
+            self.refresh().await?;
+            if let Some(planner) = self.purchases.clone() {
+                if let Err(err) = planner.buy_needed(self).await {
+                    println!("Ship purchase failed: {}", err);
+                }
+            }
+            self.context
+                .client()
+                .clock()
+                .sleep(self.config.refresh_interval)
+                .await;
+        }
+    }
+}
+
+impl Drop for FleetController {
+    fn drop(&mut self) {
+        for task in self.tasks.values() {
+            if let Some(handle) = &task.handle {
+                handle.abort();
+            }
+        }
+    }
+}
+
+/// Accepted and not yet fulfilled.
+pub fn is_active(contract: &Contract) -> bool {
+    contract.accepted && !contract.fulfilled
+}
+
+/// Whether a contract runner has something to do: work an active contract,
+/// or negotiate a new one because no offer is waiting to be accepted.
+pub fn has_contract_work(contracts: &[Contract], now: DateTime<Utc>) -> bool {
+    contracts.iter().any(is_active)
+        || !contracts
+            .iter()
+            .any(|contract| contracts::is_open_offer(contract, now))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::clock::FakeClock;
+    use crate::error::SpaceTradersError;
+    use crate::market_store::MarketStore;
+    use crate::mock_server::{MockConfig, MockServer};
+    use crate::survey_cache::SurveyCache;
+
+    /// A mock server on a fake clock, which waits pass instantly on.
+    pub(crate) async fn start() -> MockServer {
+        MockServer::start(MockConfig {
+            clock: Arc::new(FakeClock::starting_at(Utc::now())),
+            ..MockConfig::default()
+        })
+        .await
+        .unwrap()
+    }
+
+    /// What the controller would hand a ship's routine on `server`.
+    pub(crate) async fn mock_context(server: &MockServer) -> FleetContext {
+        let client = server.client();
+        let system = server.state().world().system.clone();
+        let planner = client.route_planner(&system).await.unwrap();
+        FleetContext {
+            navigator: Navigator::new(client, Arc::new(planner)),
+            market_store: MarketStore::in_memory().shared(),
+            home_system: system,
+            mining: MiningConfig::default(),
+            surveys: SurveyCache::new().shared(),
+            evaluator: ContractEvaluator::default(),
+            credit_reserve: 0,
+        }
+    }
+
+    /// A controller that leaves every ship idle and wants `count` ships
+    /// doing `behaviour`.
+    async fn controller(
+        server: &MockServer,
+        behaviour: Behaviour,
+        count: usize,
+    ) -> FleetController {
+        let config = FleetConfig {
+            roles: HashMap::new(),
+            purchases: vec![PurchaseTarget {
+                ship_type: ShipType::ShipMiningDrone,
+                behaviour,
+                count,
+            }],
+            ..FleetConfig::default()
+        };
+        let mut controller = FleetController::new(mock_context(server).await, config);
+        controller.refresh().await.unwrap();
+        controller
+    }
+
+    #[tokio::test]
+    async fn purchases_are_needed_until_enough_ships_do_the_behaviour() {
+        let server = start().await;
+        let mut controller = controller(&server, Behaviour::Idle, 3).await;
+        assert_eq!(controller.count(Behaviour::Idle), 2);
+        assert_eq!(
+            controller.needed_purchase().map(|target| target.count),
+            Some(3)
+        );
+
+        let client = server.client();
+        let bought = client
+            .buy_ship("X1-MOCK-A1", &ShipType::ShipProbe)
+            .await
+            .unwrap();
+        controller.register_purchase(bought.ship, Behaviour::Idle);
+
+        assert_eq!(controller.count(Behaviour::Idle), 3);
+        assert_eq!(controller.needed_purchase(), None);
+    }
+
+    #[tokio::test]
+    async fn stopped_tasks_are_reaped_back_to_idle() {
+        let server = start().await;
+        let mut controller = controller(&server, Behaviour::Miner, 1).await;
+        let handle = tokio::spawn(async {
+            Err(SpaceTradersError::Command {
+                message: "out of asteroids".to_string(),
+            })
+        });
+        controller.tasks.insert(
+            "MOCK-AGENT-1".to_string(),
+            ShipTask {
+                behaviour: Behaviour::Miner,
+                handle: Some(handle),
+            },
+        );
+        assert_eq!(controller.needed_purchase(), None);
+
+        while !controller.tasks["MOCK-AGENT-1"]
+            .handle
+            .as_ref()
+            .unwrap()
+            .is_finished()
+        {
+            tokio::task::yield_now().await;
+        }
+        controller.reap().await;
+
+        assert_eq!(controller.tasks["MOCK-AGENT-1"].behaviour, Behaviour::Idle);
+        assert_eq!(controller.count(Behaviour::Miner), 0);
+        assert_eq!(
+            controller.needed_purchase().map(|target| target.behaviour),
+            Some(Behaviour::Miner)
+        );
+    }
+}
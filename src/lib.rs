@@ -0,0 +1,27 @@
+pub mod cli;
+pub mod client;
+pub mod clock;
+pub mod config;
+pub mod contract_evaluator;
+pub mod contracts;
+pub mod error;
+pub mod fixtures;
+pub mod fleet;
+pub mod market_store;
+pub mod mining;
+#[cfg(feature = "mock")]
+pub mod mock_server;
+pub mod models;
+pub mod navigation;
+pub mod pagination;
+pub mod rate_limit;
+pub mod retry;
+pub mod routing;
+pub mod scheduler;
+pub mod shipyard;
+#[cfg(feature = "mock")]
+pub mod simulator;
+pub mod survey_cache;
+pub mod surveying;
+pub mod trading;
+pub mod waypoints;
@@ -0,0 +1,450 @@
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::models::{
+    Market, MarketTradeGood, MarketTransaction, MarketTransactionType, SupplyLevel, TradeSymbol,
+};
+use crate::waypoints::system_symbol_of;
+
+pub type SharedMarketStore = Arc<Mutex<MarketStore>>;
+
+/// One line of the store file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MarketRecord {
+    Snapshot {
+        waypoint_symbol: String,
+        observed_at: DateTime<Utc>,
+        trade_goods: Vec<MarketTradeGood>,
+    },
+    Transaction(MarketTransaction),
+}
+
+/// The price of one good at one market at one point in time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceObservation {
+    pub waypoint_symbol: String,
+    pub symbol: TradeSymbol,
+    pub purchase_price: u64,
+    pub sell_price: u64,
+    pub trade_volume: u32,
+    pub supply: SupplyLevel,
+    pub observed_at: DateTime<Utc>,
+}
+
+impl PriceObservation {
+    /// How old the observation is at `now`.
+    pub fn age(&self, now: DateTime<Utc>) -> Duration {
+        now - self.observed_at
+    }
+}
+
+/// Buying `symbol` at one market and selling it at another.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeOpportunity {
+    pub symbol: TradeSymbol,
+    pub buy: PriceObservation,
+    pub sell: PriceObservation,
+}
+
+impl TradeOpportunity {
+    pub fn profit_per_unit(&self) -> i64 {
+        self.sell.sell_price as i64 - self.buy.purchase_price as i64
+    }
+}
+
+/// Every market snapshot and transaction the bot has seen, kept in memory
+/// and appended to a JSON-lines file so history survives restarts.
+#[derive(Debug, Default)]
+pub struct MarketStore {
+    path: Option<PathBuf>,
+    /// Price history per (waypoint, good), oldest first.
+    prices: HashMap<(String, TradeSymbol), Vec<PriceObservation>>,
+    transactions: Vec<MarketTransaction>,
+}
+
+impl MarketStore {
+    /// A store that keeps nothing on disk.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Loads the history in `path`, creating the file on first write.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut store = MarketStore {
+            path: Some(path.clone()),
+            ..Self::default()
+        };
+        if path.exists() {
+            for line in BufReader::new(File::open(&path)?).lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                store.apply(serde_json::from_str(&line)?);
+            }
+        }
+        Ok(store)
+    }
+
+    pub fn shared(self) -> SharedMarketStore {
+        Arc::new(Mutex::new(self))
+    }
+
+    fn apply(&mut self, record: MarketRecord) {
+        match record {
+            MarketRecord::Snapshot {
+                waypoint_symbol,
+                observed_at,
+                trade_goods,
+            } => {
+                for good in trade_goods {
+                    self.prices
+                        .entry((waypoint_symbol.clone(), good.symbol.clone()))
+                        .or_default()
+                        .push(PriceObservation {
+                            waypoint_symbol: waypoint_symbol.clone(),
+                            symbol: good.symbol,
+                            purchase_price: good.purchase_price,
+                            sell_price: good.sell_price,
+                            trade_volume: good.trade_volume,
+                            supply: good.supply,
+                            observed_at,
+                        });
+                }
+            }
+            MarketRecord::Transaction(transaction) => self.transactions.push(transaction),
+        }
+    }
+
+    fn append(&mut self, record: MarketRecord) -> Result<()> {
+        if let Some(path) = &self.path {
+            let mut file = OpenOptions::new().append(true).create(true).open(path)?;
+            writeln!(file, "{}", serde_json::to_string(&record)?)?;
+        }
+        self.apply(record);
+        Ok(())
+    }
+
+    /// Records the prices in `market` as observed at `observed_at`, which
+    /// should come from the client's clock so ages are in game time. Markets
+    /// fetched without a ship present carry no prices and are ignored.
+    pub fn record_market_at(&mut self, market: &Market, observed_at: DateTime<Utc>) -> Result<()> {
+        if market.trade_goods.is_empty() {
+            return Ok(());
+        }
+        self.append(MarketRecord::Snapshot {
+            waypoint_symbol: market.symbol.clone(),
+            observed_at,
+            trade_goods: market.trade_goods.clone(),
+        })
+    }
+
+    pub fn record_transaction(&mut self, transaction: &MarketTransaction) -> Result<()> {
+        self.append(MarketRecord::Transaction(transaction.clone()))
+    }
+
+    pub fn latest_price(
+        &self,
+        waypoint_symbol: &str,
+        symbol: &TradeSymbol,
+    ) -> Option<&PriceObservation> {
+        self.prices
+            .get(&(waypoint_symbol.to_string(), symbol.clone()))
+            .and_then(|history| history.last())
+    }
+
+    pub fn price_history(
+        &self,
+        waypoint_symbol: &str,
+        symbol: &TradeSymbol,
+    ) -> &[PriceObservation] {
+        self.prices
+            .get(&(waypoint_symbol.to_string(), symbol.clone()))
+            .map_or(&[], |history| history.as_slice())
+    }
+
+    /// The most recent price of `symbol` at every market in `system` that
+    /// has been observed trading it.
+    pub fn latest_prices_in_system(
+        &self,
+        system: &str,
+        symbol: &TradeSymbol,
+    ) -> Vec<&PriceObservation> {
+        self.prices
+            .iter()
+            .filter(|((waypoint, good), _)| good == symbol && system_symbol_of(waypoint) == system)
+            .filter_map(|(_, history)| history.last())
+            .collect()
+    }
+
+    /// The market in `system` that last paid the most for `symbol`.
+    pub fn best_sell_market(
+        &self,
+        system: &str,
+        symbol: &TradeSymbol,
+    ) -> Option<&PriceObservation> {
+        self.latest_prices_in_system(system, symbol)
+            .into_iter()
+            .max_by_key(|observation| observation.sell_price)
+    }
+
+    /// The market in `system` that last sold `symbol` the cheapest.
+    pub fn best_purchase_market(
+        &self,
+        system: &str,
+        symbol: &TradeSymbol,
+    ) -> Option<&PriceObservation> {
+        self.latest_prices_in_system(system, symbol)
+            .into_iter()
+            .min_by_key(|observation| observation.purchase_price)
+    }
+
+    /// The market in `system` that would pay the most for all of `goods` at
+    /// the latest known prices, with what it would pay.
+    pub fn best_market_for(
+        &self,
+        system: &str,
+        goods: &[(TradeSymbol, u32)],
+    ) -> Option<(String, u64)> {
+        let mut totals: HashMap<&str, u64> = HashMap::new();
+        for (symbol, units) in goods {
+            for observation in self.latest_prices_in_system(system, symbol) {
+                *totals.entry(&observation.waypoint_symbol).or_default() +=
+                    observation.sell_price * *units as u64;
+            }
+        }
+        totals
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(waypoint, total)| (waypoint.to_string(), total))
+    }
+
+    /// Profitable buy-here-sell-there pairs in `system` from the latest known
+    /// prices, most profitable per unit first.
+    pub fn trade_opportunities(&self, system: &str) -> Vec<TradeOpportunity> {
+        let mut symbols: Vec<&TradeSymbol> = self
+            .prices
+            .keys()
+            .filter(|(waypoint, _)| system_symbol_of(waypoint) == system)
+            .map(|(_, symbol)| symbol)
+            .collect();
+        symbols.sort();
+        symbols.dedup();
+
+        let mut opportunities: Vec<TradeOpportunity> = symbols
+            .into_iter()
+            .filter_map(|symbol| {
+                Some(TradeOpportunity {
+                    symbol: symbol.clone(),
+                    buy: self.best_purchase_market(system, symbol)?.clone(),
+                    sell: self.best_sell_market(system, symbol)?.clone(),
+                })
+            })
+            .filter(|opportunity| opportunity.profit_per_unit() > 0)
+            .collect();
+        opportunities.sort_by_key(|opportunity| -opportunity.profit_per_unit());
+        opportunities
+    }
+
+    pub fn transactions(&self) -> &[MarketTransaction] {
+        &self.transactions
+    }
+
+    /// Total credits earned from sales minus credits spent on purchases.
+    pub fn net_trade_income(&self) -> i64 {
+        self.transactions
+            .iter()
+            .map(|transaction| match transaction.transaction_type {
+                MarketTransactionType::Sell => transaction.total_price as i64,
+                MarketTransactionType::Purchase => -(transaction.total_price as i64),
+                MarketTransactionType::Unknown(_) => 0,
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::models::MarketTradeGoodType;
+
+    fn time(minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 12, minute, 0).unwrap()
+    }
+
+    /// A market selling each good for the first price and buying it for the
+    /// second.
+    fn market(symbol: &str, goods: &[(TradeSymbol, u64, u64)]) -> Market {
+        Market {
+            symbol: symbol.to_string(),
+            exports: Vec::new(),
+            imports: Vec::new(),
+            exchange: Vec::new(),
+            transactions: Vec::new(),
+            trade_goods: goods
+                .iter()
+                .map(|(symbol, purchase_price, sell_price)| MarketTradeGood {
+                    symbol: symbol.clone(),
+                    trade_type: MarketTradeGoodType::Exchange,
+                    trade_volume: 10,
+                    supply: SupplyLevel::Moderate,
+                    activity: None,
+                    purchase_price: *purchase_price,
+                    sell_price: *sell_price,
+                })
+                .collect(),
+        }
+    }
+
+    fn transaction(kind: MarketTransactionType, total_price: u64) -> MarketTransaction {
+        MarketTransaction {
+            waypoint_symbol: "X1-T-A".to_string(),
+            ship_symbol: "SHIP-1".to_string(),
+            trade_symbol: TradeSymbol::IronOre,
+            transaction_type: kind,
+            units: 10,
+            price_per_unit: total_price / 10,
+            total_price,
+            timestamp: time(0),
+        }
+    }
+
+    /// Two markets in X1-T with iron cheap at A and copper cheap at B, and
+    /// one in another system that would beat both.
+    fn record_markets(store: &mut MarketStore) {
+        use TradeSymbol::{CopperOre, IronOre};
+        let markets = [
+            market("X1-T-A", &[(IronOre, 10, 8), (CopperOre, 50, 45)]),
+            market("X1-T-B", &[(IronOre, 30, 25), (CopperOre, 40, 35)]),
+            market("X1-U-C", &[(IronOre, 1, 100)]),
+        ];
+        for market in &markets {
+            store.record_market_at(market, time(0)).unwrap();
+        }
+    }
+
+    #[test]
+    fn best_market_for_sums_every_good() {
+        let mut store = MarketStore::in_memory();
+        record_markets(&mut store);
+
+        let goods = [(TradeSymbol::IronOre, 10), (TradeSymbol::CopperOre, 1)];
+        assert_eq!(
+            store.best_market_for("X1-T", &goods),
+            Some(("X1-T-B".to_string(), 285))
+        );
+        assert_eq!(store.best_market_for("X1-V", &goods), None);
+    }
+
+    #[test]
+    fn trade_opportunities_stay_in_system_and_rank_by_profit() {
+        let mut store = MarketStore::in_memory();
+        record_markets(&mut store);
+
+        let opportunities = store.trade_opportunities("X1-T");
+        let routes: Vec<(TradeSymbol, &str, &str, i64)> = opportunities
+            .iter()
+            .map(|opportunity| {
+                (
+                    opportunity.symbol.clone(),
+                    opportunity.buy.waypoint_symbol.as_str(),
+                    opportunity.sell.waypoint_symbol.as_str(),
+                    opportunity.profit_per_unit(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            routes,
+            vec![
+                (TradeSymbol::IronOre, "X1-T-A", "X1-T-B", 15),
+                (TradeSymbol::CopperOre, "X1-T-B", "X1-T-A", 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn latest_observation_wins_and_ages_from_when_it_was_seen() {
+        let mut store = MarketStore::in_memory();
+        record_markets(&mut store);
+        let later = market("X1-T-A", &[(TradeSymbol::IronOre, 12, 40)]);
+        store.record_market_at(&later, time(5)).unwrap();
+
+        let best = store
+            .best_sell_market("X1-T", &TradeSymbol::IronOre)
+            .unwrap();
+        assert_eq!(best.waypoint_symbol, "X1-T-A");
+        assert_eq!(best.sell_price, 40);
+        assert_eq!(best.age(time(20)), Duration::minutes(15));
+        assert_eq!(
+            store.price_history("X1-T-A", &TradeSymbol::IronOre).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn markets_without_prices_are_ignored() {
+        let mut store = MarketStore::in_memory();
+        store
+            .record_market_at(&market("X1-T-A", &[]), time(0))
+            .unwrap();
+
+        assert!(store.trade_opportunities("X1-T").is_empty());
+        assert_eq!(store.latest_price("X1-T-A", &TradeSymbol::IronOre), None);
+    }
+
+    #[test]
+    fn net_trade_income_is_sales_minus_purchases() {
+        let mut store = MarketStore::in_memory();
+        store
+            .record_transaction(&transaction(MarketTransactionType::Purchase, 100))
+            .unwrap();
+        store
+            .record_transaction(&transaction(MarketTransactionType::Sell, 250))
+            .unwrap();
+
+        assert_eq!(store.net_trade_income(), 150);
+    }
+
+    #[test]
+    fn history_survives_reopening_the_file() {
+        let path = std::env::temp_dir().join(format!(
+            "spacetraders-market-store-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        let mut store = MarketStore::open(&path).unwrap();
+        record_markets(&mut store);
+        store
+            .record_transaction(&transaction(MarketTransactionType::Sell, 250))
+            .unwrap();
+        let opportunities = store.trade_opportunities("X1-T");
+        drop(store);
+
+        let reopened = MarketStore::open(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(reopened.trade_opportunities("X1-T"), opportunities);
+        assert_eq!(reopened.net_trade_income(), 250);
+        assert_eq!(
+            reopened
+                .latest_price("X1-U-C", &TradeSymbol::IronOre)
+                .map(|observation| observation.observed_at),
+            Some(time(0))
+        );
+    }
+}
@@ -0,0 +1,467 @@
+use std::{fmt, time::Duration};
+
+use crate::contracts::units_held;
+use crate::error::{Result, SpaceTradersError};
+use crate::fleet::{is_active, FleetContext};
+use crate::models::{ExtractionYield, Ship, TradeSymbol, WaypointTraitSymbol, WaypointType};
+use crate::trading::{record_market, sell_units};
+use crate::waypoints::WaypointQuery;
+
+/// How long a miner whose hold is full of kept goods waits before checking
+/// whether they have been taken off it.
+const HOLDING_WAIT: Duration = Duration::from_secs(300);
+
+/// Which goods a miner holds on to and which it throws away.
+#[derive(Debug, Clone, Default)]
+pub struct MiningConfig {
+    /// Never sold or jettisoned, e.g. ore set aside for later. Goods still
+    /// owed on active contracts are kept on top of these, up to the units
+    /// owed, and delivered once they fill the hold.
+    pub keep: Vec<TradeSymbol>,
+    /// Jettisoned as soon as they are extracted.
+    pub jettison: Vec<TradeSymbol>,
+    /// Goods to steer extraction towards with surveys. Goods required by
+    /// active contracts are targeted on top of these.
+    pub targets: Vec<TradeSymbol>,
+}
+
+/// Where a miner is in its extract/sell/return cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MiningState {
+    /// At the asteroid, extracting until the hold is full.
+    Extracting,
+    /// Taking the hold to `market` to sell it.
+    Selling { market: String },
+    /// Heading back to the asteroid.
+    Returning,
+    /// Taking contract goods that fill the hold to `destination`.
+    Delivering { destination: String },
+    /// The hold is full of kept goods; waiting for them to be unloaded.
+    Holding,
+}
+
+impl fmt::Display for MiningState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MiningState::Extracting => write!(f, "extracting"),
+            MiningState::Selling { market } => write!(f, "selling at {}", market),
+            MiningState::Returning => write!(f, "returning"),
+            MiningState::Delivering { destination } => write!(f, "delivering to {}", destination),
+            MiningState::Holding => write!(f, "holding"),
+        }
+    }
+}
+
+fn is_full(ship: &Ship) -> bool {
+    ship.cargo.units >= ship.cargo.capacity
+}
+
+/// Units of a good an active contract still needs delivered.
+#[derive(Debug, Clone)]
+struct Owed {
+    contract_id: String,
+    symbol: TradeSymbol,
+    destination: String,
+    units: u32,
+}
+
+/// Goods in the hold that can go: nothing on the keep-list, and only what
+/// exceeds the units active contracts still need.
+fn sellable(ship: &Ship, keep: &[TradeSymbol], owed: &[Owed]) -> Vec<(TradeSymbol, u32)> {
+    ship.cargo
+        .inventory
+        .iter()
+        .filter(|cargo| !keep.contains(&cargo.symbol))
+        .map(|cargo| {
+            let needed: u32 = owed
+                .iter()
+                .filter(|owed| owed.symbol == cargo.symbol)
+                .map(|owed| owed.units)
+                .sum();
+            (cargo.symbol.clone(), cargo.units.saturating_sub(needed))
+        })
+        .filter(|(_, units)| *units > 0)
+        .collect()
+}
+
+/// A miner that extracts at an asteroid, sells full holds at whichever
+/// market in the system pays the most, refuels and comes back.
+///
+/// The state is worked out from the ship itself when the miner starts, so a
+/// restarted bot picks up where the ship actually is.
+#[derive(Debug)]
+pub struct Miner {
+    context: FleetContext,
+    ship: Ship,
+    asteroid: String,
+    state: MiningState,
+    targets: Vec<TradeSymbol>,
+}
+
+impl Miner {
+    pub fn new(context: FleetContext, ship: Ship, asteroid: String) -> Self {
+        Miner {
+            context,
+            ship,
+            asteroid,
+            state: MiningState::Extracting,
+            targets: Vec::new(),
+        }
+    }
+
+    pub fn ship(&self) -> &Ship {
+        &self.ship
+    }
+
+    pub fn state(&self) -> &MiningState {
+        &self.state
+    }
+
+    /// Every delivery still outstanding on an active contract.
+    async fn owed_goods(&self) -> Result<Vec<Owed>> {
+        let mut owed = Vec::new();
+        for contract in self.context.client().get_contracts().await? {
+            if !is_active(&contract) {
+                continue;
+            }
+            for delivery in contract.terms.deliveries {
+                if delivery.units_remaining() > 0 {
+                    owed.push(Owed {
+                        contract_id: contract.id.clone(),
+                        units: delivery.units_remaining() as u32,
+                        symbol: delivery.trade_symbol,
+                        destination: delivery.destination_symbol,
+                    });
+                }
+            }
+        }
+        Ok(owed)
+    }
+
+    /// Goods the miner may sell right now.
+    async fn sellable_goods(&self) -> Result<Vec<(TradeSymbol, u32)>> {
+        let owed = self.owed_goods().await?;
+        Ok(sellable(&self.ship, &self.context.mining.keep, &owed))
+    }
+
+    /// Refreshes the goods surveys are picked for.
+    async fn refresh_targets(&mut self) -> Result<()> {
+        let mut targets = self.context.mining.targets.clone();
+        for owed in self.owed_goods().await? {
+            if !targets.contains(&owed.symbol) {
+                targets.push(owed.symbol);
+            }
+        }
+        self.targets = targets;
+        Ok(())
+    }
+
+    /// What to do with a full hold: sell it at the best known market,
+    /// deliver it if it's all owed on a contract, or hold on to it if
+    /// everything in it is kept.
+    async fn when_full(&self) -> Result<MiningState> {
+        let owed = self.owed_goods().await?;
+        let goods = sellable(&self.ship, &self.context.mining.keep, &owed);
+        if goods.is_empty() {
+            let delivery = owed
+                .into_iter()
+                .find(|owed| units_held(&self.ship, &owed.symbol) > 0);
+            return Ok(match delivery {
+                Some(owed) => MiningState::Delivering {
+                    destination: owed.destination,
+                },
+                None => MiningState::Holding,
+            });
+        }
+        let best = self
+            .context
+            .market_store
+            .lock()
+            .unwrap()
+            .best_market_for(&self.ship.nav.system_symbol, &goods);
+        let market = match best {
+            Some((market, _)) => market,
+            None => self.nearest_marketplace().await?,
+        };
+        Ok(MiningState::Selling { market })
+    }
+
+    /// The marketplace closest to the asteroid, for when no prices are known
+    /// yet.
+    async fn nearest_marketplace(&self) -> Result<String> {
+        let client = self.context.client();
+        let system = &self.ship.nav.system_symbol;
+        let asteroid = client.get_waypoint(system, &self.asteroid).await?;
+        client
+            .find_waypoints(
+                &WaypointQuery::new(system.as_str()).with_trait(WaypointTraitSymbol::Marketplace),
+            )
+            .await?
+            .into_iter()
+            .min_by(|a, b| asteroid.distance_to(a).total_cmp(&asteroid.distance_to(b)))
+            .map(|market| market.symbol)
+            .ok_or_else(|| SpaceTradersError::NoRoute {
+                from: self.asteroid.clone(),
+                to: format!("a marketplace in {}", system),
+            })
+    }
+
+    /// Works out the state from where the ship is and what it carries.
+    async fn resume(&mut self) -> Result<MiningState> {
+        self.context
+            .navigator
+            .wait_for_arrival(&mut self.ship)
+            .await?;
+        if is_full(&self.ship) {
+            self.when_full().await
+        } else if self.ship.nav.waypoint_symbol != self.asteroid {
+            Ok(MiningState::Returning)
+        } else {
+            Ok(MiningState::Extracting)
+        }
+    }
+
+    fn transition(&mut self, next: MiningState) {
+        if next != self.state {
+            println!("{}: {} -> {}", self.ship.symbol, self.state, next);
+            self.state = next;
+        }
+    }
+
+    async fn jettison(&mut self, symbol: &TradeSymbol, units: u32) -> Result<()> {
+        self.ship.cargo = self
+            .context
+            .client()
+            .jettison_cargo(&self.ship.symbol, symbol, units)
+            .await?;
+        println!("{} jettisoned {} {}", self.ship.symbol, units, symbol);
+        Ok(())
+    }
+
+    async fn extract(&mut self) -> Result<MiningState> {
+        self.context
+            .navigator
+            .go_to(&mut self.ship, &self.asteroid)
+            .await?;
+        extract_once(&self.context, &mut self.ship, &self.targets).await?;
+
+        let junk: Vec<(TradeSymbol, u32)> = self
+            .ship
+            .cargo
+            .inventory
+            .iter()
+            .filter(|cargo| self.context.mining.jettison.contains(&cargo.symbol))
+            .map(|cargo| (cargo.symbol.clone(), cargo.units))
+            .collect();
+        for (symbol, units) in junk {
+            self.jettison(&symbol, units).await?;
+        }
+
+        if is_full(&self.ship) {
+            self.when_full().await
+        } else {
+            Ok(MiningState::Extracting)
+        }
+    }
+
+    async fn sell(&mut self, market_symbol: &str) -> Result<MiningState> {
+        let navigator = self.context.navigator.clone();
+        navigator.go_to(&mut self.ship, market_symbol).await?;
+        navigator.ensure_docked(&mut self.ship).await?;
+        let market = record_market(&self.context, &self.ship).await?;
+
+        for (symbol, units) in self.sellable_goods().await? {
+            if let Some(good) = market.trade_good(&symbol) {
+                let trade_volume = good.trade_volume.max(1);
+                sell_units(&self.context, &mut self.ship, &symbol, units, trade_volume).await?;
+                continue;
+            }
+            // Keep goods another market is known to buy; dump the rest.
+            let buyer = self
+                .context
+                .market_store
+                .lock()
+                .unwrap()
+                .best_sell_market(&self.ship.nav.system_symbol, &symbol)
+                .filter(|best| best.waypoint_symbol != market_symbol)
+                .is_some();
+            if !buyer {
+                self.jettison(&symbol, units).await?;
+            }
+        }
+
+        if market.trades(&TradeSymbol::Fuel) {
+            navigator.refuel(&mut self.ship).await?;
+        }
+        if is_full(&self.ship) {
+            self.when_full().await
+        } else {
+            Ok(MiningState::Returning)
+        }
+    }
+
+    /// Hands over every owed good in the hold that `destination` takes, and
+    /// fulfils contracts that are complete.
+    async fn deliver(&mut self, destination: &str) -> Result<MiningState> {
+        let navigator = self.context.navigator.clone();
+        let client = self.context.client().clone();
+        navigator.go_to(&mut self.ship, destination).await?;
+        navigator.ensure_docked(&mut self.ship).await?;
+        for owed in self.owed_goods().await? {
+            let units = units_held(&self.ship, &owed.symbol).min(owed.units);
+            if owed.destination != destination || units == 0 {
+                continue;
+            }
+            let result = client
+                .deliver_contract(&owed.contract_id, &self.ship.symbol, &owed.symbol, units)
+                .await?;
+            self.ship.cargo = result.cargo;
+            println!(
+                "{} delivered {} {} for contract {}",
+                self.ship.symbol, units, owed.symbol, owed.contract_id
+            );
+            let complete = result
+                .contract
+                .terms
+                .deliveries
+                .iter()
+                .all(|delivery| delivery.units_remaining() == 0);
+            if complete {
+                let result = client.fulfill_contract(&owed.contract_id).await?;
+                println!(
+                    "Fulfilled contract {}, now at {} credits",
+                    owed.contract_id, result.agent.credits
+                );
+            }
+        }
+        if is_full(&self.ship) {
+            self.when_full().await
+        } else {
+            Ok(MiningState::Returning)
+        }
+    }
+
+    async fn hold(&mut self) -> Result<MiningState> {
+        self.context.client().clock().sleep(HOLDING_WAIT).await;
+        self.ship.cargo = self
+            .context
+            .client()
+            .get_ship_cargo(&self.ship.symbol)
+            .await?;
+        if is_full(&self.ship) {
+            self.when_full().await
+        } else {
+            Ok(MiningState::Returning)
+        }
+    }
+
+    async fn step(&mut self) -> Result<MiningState> {
+        match self.state.clone() {
+            MiningState::Extracting => self.extract().await,
+            MiningState::Selling { market } => self.sell(&market).await,
+            MiningState::Returning => {
+                self.context
+                    .navigator
+                    .go_to(&mut self.ship, &self.asteroid)
+                    .await?;
+                self.refresh_targets().await?;
+                Ok(MiningState::Extracting)
+            }
+            MiningState::Delivering { destination } => self.deliver(&destination).await,
+            MiningState::Holding => self.hold().await,
+        }
+    }
+
+    /// Syncs the ship's cooldown and works out the state to pick up from.
+    pub async fn start(&mut self) -> Result<()> {
+        let scheduler = self.context.navigator.scheduler();
+        scheduler
+            .sync_cooldown(self.context.client(), &self.ship.symbol)
+            .await?;
+        self.refresh_targets().await?;
+        self.state = self.resume().await?;
+        println!("{}: resuming as {}", self.ship.symbol, self.state);
+        Ok(())
+    }
+
+    /// Carries out the current state and moves on to the next one.
+    pub async fn advance(&mut self) -> Result<()> {
+        let next = self.step().await?;
+        self.transition(next);
+        Ok(())
+    }
+
+    pub async fn run(&mut self) -> Result<()> {
+        self.start().await?;
+        loop {
+            self.advance().await?;
+        }
+    }
+}
+
+/// Extracts once at the ship's current waypoint as soon as its cooldown
+/// allows, using the best cached survey for `targets` if there is one.
+/// Returns what was mined, or `None` if the attempt has to be repeated.
+pub async fn extract_once(
+    context: &FleetContext,
+    ship: &mut Ship,
+    targets: &[TradeSymbol],
+) -> Result<Option<ExtractionYield>> {
+    let navigator = &context.navigator;
+    let scheduler = navigator.scheduler();
+    let client = context.client();
+    navigator.ensure_in_orbit(ship).await?;
+    scheduler.wait_until_ready(&ship.symbol).await;
+    let survey = context.surveys.lock().unwrap().best(
+        &ship.nav.waypoint_symbol,
+        targets,
+        client.clock().utc_now(),
+    );
+    let extraction = match &survey {
+        Some(survey) => client.extract_with_survey(&ship.symbol, survey).await,
+        None => client.extract_ores(&ship.symbol).await,
+    };
+    match extraction {
+        Err(SpaceTradersError::Cooldown { remaining_seconds }) => {
+            scheduler.record_cooldown_seconds(&ship.symbol, remaining_seconds);
+            Ok(None)
+        }
+        Err(SpaceTradersError::SurveyUnusable { message }) => {
+            if let Some(survey) = &survey {
+                println!(
+                    "{} dropping survey {}: {}",
+                    ship.symbol, survey.signature, message
+                );
+                context.surveys.lock().unwrap().discard(&survey.signature);
+            }
+            Ok(None)
+        }
+        Err(err) => Err(err),
+        Ok(extraction) => {
+            scheduler.record_cooldown(&extraction.cooldown);
+            let mined = extraction.extraction.extraction_yield;
+            println!("{} extracted {} {}", ship.symbol, mined.units, mined.symbol);
+            ship.cargo = extraction.cargo;
+            Ok(Some(mined))
+        }
+    }
+}
+
+/// The engineered asteroid in the home system that miners and surveyors
+/// work at.
+pub async fn home_asteroid(context: &FleetContext) -> Result<Option<String>> {
+    let asteroids = context
+        .client()
+        .waypoint_by_type(&context.home_system, WaypointType::EngineeredAsteroid)
+        .await?;
+    Ok(asteroids.into_iter().next().map(|asteroid| asteroid.symbol))
+}
+
+/// Mines at the home system's engineered asteroid.
+pub async fn run(context: FleetContext, ship: Ship) -> Result<()> {
+    let Some(asteroid) = home_asteroid(&context).await? else {
+        println!("No engineered asteroid in {}", context.home_system);
+        return Ok(());
+    };
+    Miner::new(context, ship, asteroid).run().await
+}
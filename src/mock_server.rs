@@ -0,0 +1,286 @@
+use std::{
+    fmt,
+    net::SocketAddr,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use axum::{
+    http::{header::RETRY_AFTER, Method, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Value};
+use tokio::{net::TcpListener, task::JoinHandle};
+
+use crate::client::ApiClient;
+use crate::clock::{Clock, SystemClock};
+use crate::error::{Result, RATE_LIMIT_ERROR};
+use crate::rate_limit::{RateLimitConfig, RateLimiter};
+
+mod routes;
+pub mod state;
+pub mod world;
+
+pub use state::MockState;
+pub use world::World;
+
+/// How the mock server's game behaves.
+#[derive(Debug, Clone)]
+pub struct MockConfig {
+    pub system: String,
+    /// The agent the server starts with, before anyone calls `/register`.
+    pub agent_symbol: String,
+    pub faction: String,
+    pub starting_credits: u64,
+    pub extract_cooldown: Duration,
+    pub survey_cooldown: Duration,
+    /// Units yielded by one extraction, if the hold has room.
+    pub extraction_units: u32,
+    /// How long surveys stay usable.
+    pub survey_lifetime: Duration,
+    /// Generates a random system from this seed instead of the fixed
+    /// layout, and varies yields and contract terms with it.
+    pub seed: Option<u64>,
+    /// How strongly prices react to our trades; 0 keeps them fixed.
+    pub market_elasticity: f64,
+    /// Time for a market to recover half of the price movement we caused.
+    pub market_recovery: Duration,
+    /// Source of game time: arrivals, cooldowns, expirations.
+    pub clock: Arc<dyn Clock>,
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        MockConfig {
+            system: "X1-MOCK".to_string(),
+            agent_symbol: "MOCK-AGENT".to_string(),
+            faction: "COSMIC".to_string(),
+            starting_credits: 175_000,
+            extract_cooldown: Duration::from_secs(70),
+            survey_cooldown: Duration::from_secs(60),
+            extraction_units: 10,
+            survey_lifetime: Duration::from_secs(15 * 60),
+            seed: None,
+            market_elasticity: 0.0,
+            market_recovery: Duration::from_secs(6 * 3600),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+/// An error in the API's `{"error": {...}}` envelope.
+#[derive(Debug, Clone)]
+pub struct MockError {
+    pub status: u16,
+    pub code: u32,
+    pub message: String,
+    pub data: Option<Value>,
+    /// Seconds sent in a `Retry-After` header.
+    pub retry_after: Option<u64>,
+}
+
+pub type MockResult<T> = std::result::Result<T, MockError>;
+
+impl MockError {
+    pub fn api(status: u16, code: u32, message: impl Into<String>) -> Self {
+        MockError {
+            status,
+            code,
+            message: message.into(),
+            data: None,
+            retry_after: None,
+        }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::api(404, 404, message)
+    }
+
+    /// A request the API would reject as malformed.
+    pub fn invalid(message: impl Into<String>) -> Self {
+        Self::api(422, 422, message)
+    }
+
+    pub fn unauthorized() -> Self {
+        Self::api(401, 4100, "Missing or invalid bearer token")
+    }
+
+    pub fn rate_limited(retry_after: u64) -> Self {
+        let mut error = Self::api(429, RATE_LIMIT_ERROR, "Rate limit exceeded")
+            .with_data(json!({ "retryAfter": retry_after }));
+        error.retry_after = Some(retry_after);
+        error
+    }
+
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+}
+
+impl fmt::Display for MockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (HTTP {}): {}", self.code, self.status, self.message)
+    }
+}
+
+impl IntoResponse for MockError {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut body = json!({ "code": self.code, "message": self.message });
+        if let Some(data) = self.data {
+            body["data"] = data;
+        }
+        let mut response = (status, Json(json!({ "error": body }))).into_response();
+        if let Some(seconds) = self.retry_after {
+            response.headers_mut().insert(RETRY_AFTER, seconds.into());
+        }
+        response
+    }
+}
+
+/// An error the server returns instead of handling a matching request, e.g.
+/// to exercise retries or a rare API failure.
+#[derive(Debug, Clone)]
+pub struct InjectedError {
+    /// Only requests with this method match; any method if `None`.
+    pub method: Option<Method>,
+    /// Requests whose path (without the query string) starts with this match.
+    pub path_prefix: String,
+    /// How many more matching requests fail.
+    pub times: usize,
+    pub error: MockError,
+}
+
+impl InjectedError {
+    /// Fails the next request under `path_prefix` with `error`.
+    pub fn new(path_prefix: impl Into<String>, error: MockError) -> Self {
+        InjectedError {
+            method: None,
+            path_prefix: path_prefix.into(),
+            times: 1,
+            error,
+        }
+    }
+
+    pub fn method(mut self, method: Method) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    pub fn times(mut self, times: usize) -> Self {
+        self.times = times;
+        self
+    }
+
+    fn matches(&self, method: &Method, path: &str) -> bool {
+        self.times > 0
+            && self.method.as_ref().is_none_or(|wanted| wanted == method)
+            && path.starts_with(&self.path_prefix)
+    }
+}
+
+/// State shared between the server task and the test driving it.
+#[derive(Debug)]
+pub(crate) struct Shared {
+    state: Mutex<MockState>,
+    injected: Mutex<Vec<InjectedError>>,
+    requests: Mutex<Vec<String>>,
+}
+
+impl Shared {
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Logs the request and takes the first injected error it matches.
+    fn intercept(&self, method: &Method, path: &str) -> Option<MockError> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", method, path));
+        let mut injected = self.injected.lock().unwrap();
+        let index = injected
+            .iter()
+            .position(|injected| injected.matches(method, path))?;
+        injected[index].times -= 1;
+        let error = injected[index].error.clone();
+        if injected[index].times == 0 {
+            injected.remove(index);
+        }
+        Some(error)
+    }
+}
+
+/// An in-process stand-in for the SpaceTraders API, serving the endpoints the
+/// bot uses from a deterministic game on a local port. Automation can run
+/// against it without network access; dropping the server stops it.
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+    task: JoinHandle<()>,
+}
+
+impl MockServer {
+    pub async fn start(config: MockConfig) -> Result<Self> {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(MockState::new(config)),
+            injected: Mutex::new(Vec::new()),
+            requests: Mutex::new(Vec::new()),
+        });
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let app = routes::router(shared.clone());
+        let task = tokio::spawn(async move {
+            if let Err(err) = axum::serve(listener, app).await {
+                println!("Mock server stopped: {}", err);
+            }
+        });
+        Ok(MockServer { addr, shared, task })
+    }
+
+    /// Base URL to point an `ApiClient` at.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// A client for the server's agent, sharing the game clock and with a
+    /// rate limit loose enough never to get in the way.
+    pub fn client(&self) -> ApiClient {
+        let state = self.state();
+        let limiter = RateLimiter::new(
+            RateLimitConfig {
+                per_second: 1_000.0,
+                burst: 1_000,
+                burst_window: Duration::from_secs(1),
+            },
+            state.config().clock.clone(),
+        );
+        ApiClient::new(self.url())
+            .with_rate_limiter(Arc::new(limiter))
+            .with_token(state.token())
+    }
+
+    /// Direct access to the game, for setting up scenarios and checking
+    /// results. Don't hold the guard across an `.await`.
+    pub fn state(&self) -> MutexGuard<'_, MockState> {
+        self.shared.state()
+    }
+
+    pub fn inject_error(&self, error: InjectedError) {
+        self.shared.injected.lock().unwrap().push(error);
+    }
+
+    /// Every request served so far, as `"METHOD /path"`.
+    pub fn requests(&self) -> Vec<String> {
+        self.shared.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -0,0 +1,354 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, Request, State},
+    http::{header::AUTHORIZATION, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, patch, post},
+    Json, Router,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+
+use super::{MockError, MockResult, Shared};
+use crate::models::{ShipNavFlightMode, ShipType, Survey, TradeSymbol};
+use crate::pagination::PAGE_LIMIT;
+
+type Reply = MockResult<Response>;
+type Params = Query<Vec<(String, String)>>;
+
+pub(crate) fn router(shared: Arc<Shared>) -> Router {
+    let ship = Router::new()
+        .route("/", get(ship))
+        .route("/cargo", get(cargo))
+        .route("/cooldown", get(cooldown))
+        .route("/orbit", post(orbit))
+        .route("/dock", post(dock))
+        .route("/nav", patch(flight_mode))
+        .route("/navigate", post(navigate))
+        .route("/refuel", post(refuel))
+        .route("/extract", post(extract))
+        .route("/extract/survey", post(extract_with_survey))
+        .route("/survey", post(survey))
+        .route("/sell", post(sell))
+        .route("/purchase", post(purchase))
+        .route("/jettison", post(jettison))
+        .route("/negotiate/contract", post(negotiate));
+    Router::new()
+        .route("/register", post(register))
+        .route("/my/agent", get(agent))
+        .route("/my/contracts", get(contracts))
+        .route("/my/contracts/{id}/accept", post(accept))
+        .route("/my/contracts/{id}/deliver", post(deliver))
+        .route("/my/contracts/{id}/fulfill", post(fulfill))
+        .route("/my/ships", get(ships).post(buy_ship))
+        .nest("/my/ships/{ship}", ship)
+        .route("/systems/{system}", get(system))
+        .route("/systems/{system}/waypoints", get(waypoints))
+        .route("/systems/{system}/waypoints/{waypoint}", get(waypoint))
+        .route("/systems/{system}/waypoints/{waypoint}/market", get(market))
+        .route(
+            "/systems/{system}/waypoints/{waypoint}/shipyard",
+            get(shipyard),
+        )
+        .layer(middleware::from_fn_with_state(shared.clone(), guard))
+        .with_state(shared)
+}
+
+/// Serves injected errors and turns away `/my` requests without the agent's
+/// token before any handler runs.
+async fn guard(State(shared): State<Arc<Shared>>, request: Request, next: Next) -> Response {
+    let path = request.uri().path().to_string();
+    if let Some(error) = shared.intercept(request.method(), &path) {
+        return error.into_response();
+    }
+    if path.starts_with("/my/") {
+        let expected = format!("Bearer {}", shared.state().token());
+        let authorized = request
+            .headers()
+            .get(AUTHORIZATION)
+            .is_some_and(|header| header.as_bytes() == expected.as_bytes());
+        if !authorized {
+            return MockError::unauthorized().into_response();
+        }
+    }
+    next.run(request).await
+}
+
+fn data(result: MockResult<impl Serialize>) -> Reply {
+    Ok(Json(json!({ "data": result? })).into_response())
+}
+
+fn created(result: MockResult<impl Serialize>) -> Reply {
+    Ok((StatusCode::CREATED, Json(json!({ "data": result? }))).into_response())
+}
+
+/// One page of `items`, honouring the `page` and `limit` query parameters.
+fn page(result: MockResult<Vec<impl Serialize>>, params: &[(String, String)]) -> Reply {
+    let items = result?;
+    let number = |name: &str, default: u32| -> MockResult<u32> {
+        match params.iter().find(|(key, _)| key == name) {
+            Some((_, value)) => value
+                .parse()
+                .map_err(|_| MockError::invalid(format!("{} must be a number", name))),
+            None => Ok(default),
+        }
+    };
+    let page = number("page", 1)?.max(1);
+    let limit = number("limit", 10)?;
+    if limit == 0 || limit > PAGE_LIMIT {
+        return Err(MockError::invalid(format!(
+            "limit must be between 1 and {}",
+            PAGE_LIMIT
+        )));
+    }
+    let total = items.len();
+    let items: Vec<_> = items
+        .into_iter()
+        .skip(((page - 1) * limit) as usize)
+        .take(limit as usize)
+        .collect();
+    Ok(Json(json!({
+        "data": items,
+        "meta": { "total": total, "page": page, "limit": limit },
+    }))
+    .into_response())
+}
+
+fn parse<T: DeserializeOwned>(body: &Bytes) -> MockResult<T> {
+    serde_json::from_slice(body)
+        .map_err(|err| MockError::invalid(format!("Invalid request body: {}", err)))
+}
+
+#[derive(Deserialize)]
+struct RegisterRequest {
+    symbol: String,
+    faction: String,
+}
+
+#[derive(Deserialize)]
+struct DeliverRequest {
+    #[serde(rename = "shipSymbol")]
+    ship_symbol: String,
+    #[serde(rename = "tradeSymbol")]
+    trade_symbol: TradeSymbol,
+    units: u32,
+}
+
+#[derive(Deserialize)]
+struct CargoRequest {
+    symbol: TradeSymbol,
+    units: u32,
+}
+
+#[derive(Deserialize)]
+struct NavigateRequest {
+    #[serde(rename = "waypointSymbol")]
+    waypoint_symbol: String,
+}
+
+#[derive(Deserialize)]
+struct FlightModeRequest {
+    #[serde(rename = "flightMode")]
+    flight_mode: ShipNavFlightMode,
+}
+
+#[derive(Deserialize)]
+struct BuyShipRequest {
+    #[serde(rename = "shipType")]
+    ship_type: ShipType,
+    #[serde(rename = "waypointSymbol")]
+    waypoint_symbol: String,
+}
+
+async fn register(State(shared): State<Arc<Shared>>, body: Bytes) -> Reply {
+    let request: RegisterRequest = parse(&body)?;
+    created(shared.state().register(&request.symbol, &request.faction))
+}
+
+async fn agent(State(shared): State<Arc<Shared>>) -> Reply {
+    data(Ok(shared.state().agent().clone()))
+}
+
+async fn contracts(State(shared): State<Arc<Shared>>, Query(params): Params) -> Reply {
+    page(Ok(shared.state().contracts().to_vec()), &params)
+}
+
+async fn accept(State(shared): State<Arc<Shared>>, Path(id): Path<String>) -> Reply {
+    data(shared.state().accept_contract(&id))
+}
+
+async fn deliver(State(shared): State<Arc<Shared>>, Path(id): Path<String>, body: Bytes) -> Reply {
+    let request: DeliverRequest = parse(&body)?;
+    data(shared.state().deliver_contract(
+        &id,
+        &request.ship_symbol,
+        &request.trade_symbol,
+        request.units,
+    ))
+}
+
+async fn fulfill(State(shared): State<Arc<Shared>>, Path(id): Path<String>) -> Reply {
+    data(shared.state().fulfill_contract(&id))
+}
+
+async fn ships(State(shared): State<Arc<Shared>>, Query(params): Params) -> Reply {
+    page(Ok(shared.state().ships()), &params)
+}
+
+async fn buy_ship(State(shared): State<Arc<Shared>>, body: Bytes) -> Reply {
+    let request: BuyShipRequest = parse(&body)?;
+    created(
+        shared
+            .state()
+            .buy_ship(&request.ship_type, &request.waypoint_symbol),
+    )
+}
+
+async fn ship(State(shared): State<Arc<Shared>>, Path(ship): Path<String>) -> Reply {
+    data(shared.state().ship(&ship).map(|ship| ship.clone()))
+}
+
+async fn cargo(State(shared): State<Arc<Shared>>, Path(ship): Path<String>) -> Reply {
+    data(shared.state().cargo(&ship))
+}
+
+/// 204 No Content once the ship is ready, like the real API.
+async fn cooldown(State(shared): State<Arc<Shared>>, Path(ship): Path<String>) -> Reply {
+    match shared.state().cooldown(&ship)? {
+        Some(cooldown) => data(Ok(cooldown)),
+        None => Ok(StatusCode::NO_CONTENT.into_response()),
+    }
+}
+
+async fn orbit(State(shared): State<Arc<Shared>>, Path(ship): Path<String>) -> Reply {
+    data(shared.state().orbit(&ship))
+}
+
+async fn dock(State(shared): State<Arc<Shared>>, Path(ship): Path<String>) -> Reply {
+    data(shared.state().dock(&ship))
+}
+
+async fn flight_mode(
+    State(shared): State<Arc<Shared>>,
+    Path(ship): Path<String>,
+    body: Bytes,
+) -> Reply {
+    let request: FlightModeRequest = parse(&body)?;
+    data(shared.state().set_flight_mode(&ship, request.flight_mode))
+}
+
+async fn navigate(
+    State(shared): State<Arc<Shared>>,
+    Path(ship): Path<String>,
+    body: Bytes,
+) -> Reply {
+    let request: NavigateRequest = parse(&body)?;
+    data(shared.state().navigate(&ship, &request.waypoint_symbol))
+}
+
+async fn refuel(State(shared): State<Arc<Shared>>, Path(ship): Path<String>) -> Reply {
+    data(shared.state().refuel(&ship))
+}
+
+async fn extract(State(shared): State<Arc<Shared>>, Path(ship): Path<String>) -> Reply {
+    created(shared.state().extract(&ship, None))
+}
+
+async fn extract_with_survey(
+    State(shared): State<Arc<Shared>>,
+    Path(ship): Path<String>,
+    body: Bytes,
+) -> Reply {
+    let survey: Survey = parse(&body)?;
+    created(shared.state().extract(&ship, Some(&survey)))
+}
+
+async fn survey(State(shared): State<Arc<Shared>>, Path(ship): Path<String>) -> Reply {
+    created(shared.state().survey(&ship))
+}
+
+async fn sell(State(shared): State<Arc<Shared>>, Path(ship): Path<String>, body: Bytes) -> Reply {
+    let request: CargoRequest = parse(&body)?;
+    created(shared.state().sell(&ship, &request.symbol, request.units))
+}
+
+async fn purchase(
+    State(shared): State<Arc<Shared>>,
+    Path(ship): Path<String>,
+    body: Bytes,
+) -> Reply {
+    let request: CargoRequest = parse(&body)?;
+    created(
+        shared
+            .state()
+            .purchase(&ship, &request.symbol, request.units),
+    )
+}
+
+async fn jettison(
+    State(shared): State<Arc<Shared>>,
+    Path(ship): Path<String>,
+    body: Bytes,
+) -> Reply {
+    let request: CargoRequest = parse(&body)?;
+    data(
+        shared
+            .state()
+            .jettison(&ship, &request.symbol, request.units),
+    )
+}
+
+async fn negotiate(State(shared): State<Arc<Shared>>, Path(ship): Path<String>) -> Reply {
+    created(shared.state().negotiate_contract(&ship))
+}
+
+async fn system(State(shared): State<Arc<Shared>>, Path(system): Path<String>) -> Reply {
+    data(shared.state().system(&system))
+}
+
+/// Supports the `type` filter and any number of `traits`, all of which must
+/// match.
+async fn waypoints(
+    State(shared): State<Arc<Shared>>,
+    Path(system): Path<String>,
+    Query(params): Params,
+) -> Reply {
+    let waypoints = shared.state().waypoints(&system).map(|waypoints| {
+        waypoints
+            .into_iter()
+            .filter(|waypoint| {
+                params.iter().all(|(key, value)| match key.as_str() {
+                    "type" => waypoint.waypoint_type.as_str() == value,
+                    "traits" => waypoint.has_trait(&value.as_str().into()),
+                    _ => true,
+                })
+            })
+            .collect::<Vec<_>>()
+    });
+    page(waypoints, &params)
+}
+
+async fn waypoint(
+    State(shared): State<Arc<Shared>>,
+    Path((_, waypoint)): Path<(String, String)>,
+) -> Reply {
+    let waypoint = shared.state().waypoint(&waypoint).cloned();
+    data(waypoint.ok_or_else(|| MockError::not_found("Waypoint not found")))
+}
+
+async fn market(
+    State(shared): State<Arc<Shared>>,
+    Path((_, waypoint)): Path<(String, String)>,
+) -> Reply {
+    data(shared.state().market(&waypoint))
+}
+
+async fn shipyard(
+    State(shared): State<Arc<Shared>>,
+    Path((_, waypoint)): Path<(String, String)>,
+) -> Reply {
+    data(shared.state().shipyard(&waypoint))
+}
@@ -0,0 +1,1081 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde_json::{json, Value};
+
+use super::world::{self, World};
+use super::{MockConfig, MockError, MockResult};
+use crate::error::{
+    COOLDOWN_CONFLICT_ERROR, MARKET_TRADE_INSUFFICIENT_CREDITS_ERROR,
+    NAVIGATE_INSUFFICIENT_FUEL_ERROR, NAVIGATE_IN_TRANSIT_ERROR, PURCHASE_SHIP_CREDITS_ERROR,
+    SHIP_IN_TRANSIT_ERROR, SHIP_NOT_DOCKED_ERROR, SHIP_NOT_IN_ORBIT_ERROR,
+    SHIP_SURVEY_EXHAUSTED_ERROR, SHIP_SURVEY_EXPIRATION_ERROR, SHIP_SURVEY_VERIFICATION_ERROR,
+};
+use crate::models::{
+    AgentData, AvailableShips, Cargo, CargoObject, Contract, ContractType, Cooldown, Delivery,
+    Market, MarketTradeGood, MarketTransaction, MarketTransactionType, Payment, Ship,
+    ShipNavFlightMode, ShipNavRouteWaypoint, ShipNavStatus, ShipType, ShipyardShipType,
+    SupplyLevel, Survey, SurveyDeposit, SurveySize, System, SystemWaypoint, Terms, TradeSymbol,
+    Transaction, Waypoint,
+};
+use crate::routing;
+
+const NAVIGATE_SAME_DESTINATION_ERROR: u32 = 4204;
+const EXTRACT_INVALID_WAYPOINT_ERROR: u32 = 4205;
+const CARGO_MISSING_ERROR: u32 = 4219;
+const SHIPYARD_NOT_SELLING_ERROR: u32 = 4222;
+const CARGO_EXCEEDS_LIMIT_ERROR: u32 = 4228;
+const SHIP_MISSING_MOUNT_ERROR: u32 = 4243;
+const CONTRACT_NOT_ACCEPTED_ERROR: u32 = 4501;
+const CONTRACT_DELIVER_INVALID_ERROR: u32 = 4502;
+const CONTRACT_ACCEPTED_ERROR: u32 = 4503;
+const CONTRACT_TERMS_NOT_MET_ERROR: u32 = 4504;
+const CONTRACT_ALREADY_ACTIVE_ERROR: u32 = 4511;
+const MARKET_NOT_TRADED_ERROR: u32 = 4602;
+const MARKET_TRADE_UNIT_LIMIT_ERROR: u32 = 4604;
+
+/// Recent transactions kept per market.
+const MARKET_TRANSACTIONS: usize = 20;
+/// Tank units bought per market unit of fuel.
+const FUEL_PER_MARKET_UNIT: u32 = 100;
+
+/// How much more than market value a contract pays.
+const CONTRACT_PREMIUM: u64 = 2;
+
+/// How far our trades have pushed the price of one good at one market:
+/// positive after selling into it, negative after buying it up, measured in
+/// trade volumes.
+#[derive(Debug, Clone, Copy)]
+struct Pressure {
+    level: f64,
+    at: DateTime<Utc>,
+}
+
+/// The whole game as the mock server sees it: one agent, its ships and
+/// contracts, and the world they live in. Every operation is synchronous and
+/// deterministic, with time taken from the configured clock.
+#[derive(Debug)]
+pub struct MockState {
+    config: MockConfig,
+    world: World,
+    token: String,
+    agent: AgentData,
+    ships: BTreeMap<String, Ship>,
+    contracts: Vec<Contract>,
+    /// Surveys handed out, with the extractions left in each.
+    surveys: HashMap<String, (Survey, u32)>,
+    pressure: HashMap<(String, TradeSymbol), Pressure>,
+    /// Set when the config has a seed; drives yields and contract terms.
+    rng: Option<StdRng>,
+    extractions: usize,
+    surveys_made: usize,
+    contracts_made: usize,
+}
+
+impl MockState {
+    pub fn new(config: MockConfig) -> Self {
+        let world = match config.seed {
+            Some(seed) => world::generate(&config.system, seed),
+            None => world::build(&config.system),
+        };
+        let mut state = MockState {
+            agent: AgentData {
+                account_id: String::new(),
+                credits: 0,
+                headquarters: String::new(),
+                ship_count: 0,
+                starting_faction: String::new(),
+                symbol: String::new(),
+            },
+            token: String::new(),
+            ships: BTreeMap::new(),
+            contracts: Vec::new(),
+            surveys: HashMap::new(),
+            pressure: HashMap::new(),
+            rng: config.seed.map(StdRng::seed_from_u64),
+            extractions: 0,
+            surveys_made: 0,
+            contracts_made: 0,
+            world,
+            config,
+        };
+        let (symbol, faction) = (
+            state.config.agent_symbol.clone(),
+            state.config.faction.clone(),
+        );
+        state.start_agent(&symbol, &faction);
+        state
+    }
+
+    pub fn config(&self) -> &MockConfig {
+        &self.config
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.config.clock.utc_now()
+    }
+
+    /// Replaces the agent with a fresh one: starting credits, a command
+    /// frigate and a probe at headquarters, and one contract on offer.
+    fn start_agent(&mut self, symbol: &str, faction: &str) {
+        let headquarters = self.world.headquarters();
+        self.token = format!("mock-token-{}", symbol);
+        self.agent = AgentData {
+            account_id: format!("mock-account-{}", symbol),
+            credits: self.config.starting_credits,
+            headquarters,
+            ship_count: 0,
+            starting_faction: faction.to_string(),
+            symbol: symbol.to_string(),
+        };
+        self.ships.clear();
+        self.contracts.clear();
+        self.surveys.clear();
+        let headquarters = self.waypoint(&self.agent.headquarters).unwrap().clone();
+        for ship_type in [ShipType::ShipCommandFrigate, ShipType::ShipProbe] {
+            self.add_ship(&ship_type, &headquarters);
+        }
+        self.offer_contract();
+    }
+
+    fn add_ship(&mut self, ship_type: &ShipType, location: &Waypoint) -> Ship {
+        self.agent.ship_count += 1;
+        let symbol = format!("{}-{:X}", self.agent.symbol, self.agent.ship_count);
+        let ship = world::ship(
+            ship_type,
+            &symbol,
+            &self.agent.starting_faction,
+            location,
+            self.now(),
+        );
+        self.ships.insert(symbol, ship.clone());
+        ship
+    }
+
+    /// Offers the next procurement contract: one good a market in the
+    /// system imports and that can be mined here, at a premium over its price.
+    fn offer_contract(&mut self) -> Contract {
+        let candidates = self.world.contract_goods();
+        let (index, units) = match &mut self.rng {
+            Some(rng) => (
+                rng.gen_range(0..candidates.len()),
+                rng.gen_range(3..=9) * 10,
+            ),
+            None => (
+                self.contracts_made,
+                40 + (self.contracts_made as u64 % 3) * 10,
+            ),
+        };
+        let (symbol, destination, price) = candidates[index % candidates.len()].clone();
+        let payment = units * price * CONTRACT_PREMIUM;
+        self.contracts_made += 1;
+        let now = self.now();
+        let contract = Contract {
+            accepted: false,
+            deadline_to_accept: now + chrono::Duration::days(1),
+            expiration_date: now + chrono::Duration::days(1),
+            faction_symbol: self.agent.starting_faction.clone(),
+            fulfilled: false,
+            id: format!("mock-contract-{}", self.contracts_made),
+            terms: Terms {
+                deadline: now + chrono::Duration::days(7),
+                deliveries: vec![Delivery {
+                    destination_symbol: destination,
+                    trade_symbol: symbol,
+                    units_fulfilled: 0,
+                    units_required: units,
+                }],
+                payment: Payment {
+                    payment_on_accepted: payment / 5,
+                    payment_on_fulfilled: payment - payment / 5,
+                },
+            },
+            contract_type: ContractType::Procurement,
+        };
+        self.contracts.push(contract.clone());
+        contract
+    }
+
+    pub fn register(&mut self, symbol: &str, faction: &str) -> MockResult<Value> {
+        if symbol.len() < 3 || symbol.len() > 14 {
+            return Err(MockError::invalid(
+                "Agent symbol must be 3 to 14 characters long",
+            ));
+        }
+        self.start_agent(symbol, faction);
+        let ships: Vec<&Ship> = self.ships.values().collect();
+        Ok(json!({
+            "token": self.token,
+            "agent": self.agent,
+            "contract": self.contracts[0],
+            "faction": { "symbol": faction },
+            "ships": ships,
+        }))
+    }
+
+    pub fn agent(&self) -> &AgentData {
+        &self.agent
+    }
+
+    pub fn set_credits(&mut self, credits: u64) {
+        self.agent.credits = credits;
+    }
+
+    pub fn contracts(&self) -> &[Contract] {
+        &self.contracts
+    }
+
+    fn contract_mut(&mut self, id: &str) -> MockResult<&mut Contract> {
+        self.contracts
+            .iter_mut()
+            .find(|contract| contract.id == id)
+            .ok_or_else(|| MockError::not_found(format!("Contract {} not found", id)))
+    }
+
+    pub fn accept_contract(&mut self, id: &str) -> MockResult<Value> {
+        let now = self.now();
+        let contract = self.contract_mut(id)?;
+        if contract.accepted {
+            return Err(MockError::api(
+                400,
+                CONTRACT_ACCEPTED_ERROR,
+                format!("Contract {} has already been accepted", id),
+            ));
+        }
+        if contract.deadline_to_accept <= now {
+            return Err(MockError::api(
+                400,
+                CONTRACT_ACCEPTED_ERROR,
+                format!("Contract {} can no longer be accepted", id),
+            ));
+        }
+        contract.accepted = true;
+        let contract = contract.clone();
+        self.agent.credits += contract.terms.payment.payment_on_accepted;
+        Ok(json!({ "agent": self.agent, "contract": contract }))
+    }
+
+    pub fn deliver_contract(
+        &mut self,
+        id: &str,
+        ship_symbol: &str,
+        trade_symbol: &TradeSymbol,
+        units: u32,
+    ) -> MockResult<Value> {
+        let contract = self.contract_mut(id)?.clone();
+        if !contract.accepted || contract.fulfilled {
+            return Err(MockError::api(
+                400,
+                CONTRACT_NOT_ACCEPTED_ERROR,
+                format!("Contract {} is not active", id),
+            ));
+        }
+        let ship = self.docked_ship(ship_symbol)?;
+        let Some(index) = contract
+            .terms
+            .deliveries
+            .iter()
+            .position(|delivery| &delivery.trade_symbol == trade_symbol)
+        else {
+            return Err(MockError::api(
+                400,
+                CONTRACT_DELIVER_INVALID_ERROR,
+                format!("Contract {} does not require {}", id, trade_symbol),
+            ));
+        };
+        let delivery = &contract.terms.deliveries[index];
+        if delivery.destination_symbol != ship.nav.waypoint_symbol {
+            return Err(MockError::api(
+                400,
+                CONTRACT_DELIVER_INVALID_ERROR,
+                format!(
+                    "{} must be delivered to {}",
+                    trade_symbol, delivery.destination_symbol
+                ),
+            ));
+        }
+        if units as u64 > delivery.units_remaining() {
+            return Err(MockError::api(
+                400,
+                CONTRACT_DELIVER_INVALID_ERROR,
+                format!(
+                    "Only {} more units of {} are required",
+                    delivery.units_remaining(),
+                    trade_symbol
+                ),
+            ));
+        }
+        remove_cargo(ship, trade_symbol, units)?;
+        let cargo = ship.cargo.clone();
+        let contract = self.contract_mut(id)?;
+        contract.terms.deliveries[index].units_fulfilled += units as u64;
+        Ok(json!({ "contract": contract, "cargo": cargo }))
+    }
+
+    pub fn fulfill_contract(&mut self, id: &str) -> MockResult<Value> {
+        let contract = self.contract_mut(id)?;
+        if !contract.accepted || contract.fulfilled {
+            return Err(MockError::api(
+                400,
+                CONTRACT_NOT_ACCEPTED_ERROR,
+                format!("Contract {} is not active", id),
+            ));
+        }
+        if contract
+            .terms
+            .deliveries
+            .iter()
+            .any(|delivery| delivery.units_remaining() > 0)
+        {
+            return Err(MockError::api(
+                400,
+                CONTRACT_TERMS_NOT_MET_ERROR,
+                format!("Contract {} has undelivered goods", id),
+            ));
+        }
+        contract.fulfilled = true;
+        let contract = contract.clone();
+        self.agent.credits += contract.terms.payment.payment_on_fulfilled;
+        Ok(json!({ "agent": self.agent, "contract": contract }))
+    }
+
+    pub fn negotiate_contract(&mut self, ship_symbol: &str) -> MockResult<Value> {
+        let headquarters = self.agent.headquarters.clone();
+        let ship = self.docked_ship(ship_symbol)?;
+        if ship.nav.waypoint_symbol != headquarters {
+            return Err(MockError::invalid(format!(
+                "Contracts can only be negotiated at {}",
+                headquarters
+            )));
+        }
+        let now = self.now();
+        let busy = self.contracts.iter().any(|contract| {
+            !contract.fulfilled && (contract.accepted || contract.deadline_to_accept > now)
+        });
+        if busy {
+            return Err(MockError::api(
+                400,
+                CONTRACT_ALREADY_ACTIVE_ERROR,
+                "Agent already has an active or open contract",
+            ));
+        }
+        Ok(json!({ "contract": self.offer_contract() }))
+    }
+
+    /// Lands ships whose arrival time has passed and winds down cooldowns.
+    fn settle(&mut self) {
+        let now = self.now();
+        for ship in self.ships.values_mut() {
+            if ship.nav.status == ShipNavStatus::InTransit && ship.nav.route.arrival <= now {
+                ship.nav.status = ShipNavStatus::InOrbit;
+            }
+            match ship.cooldown.expiration {
+                Some(expiration) if expiration > now => {
+                    ship.cooldown.remaining_seconds = (expiration - now).num_seconds().max(1) as u64
+                }
+                _ => {
+                    ship.cooldown.remaining_seconds = 0;
+                    ship.cooldown.expiration = None;
+                }
+            }
+        }
+    }
+
+    pub fn ships(&mut self) -> Vec<Ship> {
+        self.settle();
+        self.ships.values().cloned().collect()
+    }
+
+    pub fn ship(&mut self, symbol: &str) -> MockResult<&mut Ship> {
+        self.settle();
+        self.ships
+            .get_mut(symbol)
+            .ok_or_else(|| MockError::not_found(format!("Ship {} not found", symbol)))
+    }
+
+    /// The ship, failing if it is still in transit.
+    fn landed_ship(&mut self, symbol: &str, code: u32) -> MockResult<&mut Ship> {
+        let now = self.now();
+        let ship = self.ship(symbol)?;
+        if ship.nav.status == ShipNavStatus::InTransit {
+            let seconds = (ship.nav.route.arrival - now).num_seconds().max(1);
+            return Err(
+                MockError::api(400, code, format!("Ship {} is in transit", symbol))
+                    .with_data(json!({ "secondsToArrival": seconds })),
+            );
+        }
+        Ok(ship)
+    }
+
+    fn docked_ship(&mut self, symbol: &str) -> MockResult<&mut Ship> {
+        let ship = self.landed_ship(symbol, SHIP_IN_TRANSIT_ERROR)?;
+        if ship.nav.status != ShipNavStatus::Docked {
+            return Err(MockError::api(
+                400,
+                SHIP_NOT_DOCKED_ERROR,
+                format!("Ship {} must be docked", symbol),
+            ));
+        }
+        Ok(ship)
+    }
+
+    fn orbiting_ship(&mut self, symbol: &str, code: u32) -> MockResult<&mut Ship> {
+        let ship = self.landed_ship(symbol, code)?;
+        if ship.nav.status != ShipNavStatus::InOrbit {
+            return Err(MockError::api(
+                400,
+                SHIP_NOT_IN_ORBIT_ERROR,
+                format!("Ship {} must be in orbit", symbol),
+            ));
+        }
+        Ok(ship)
+    }
+
+    /// The ship, failing if its reactor is still cooling down.
+    fn ready_ship(&mut self, symbol: &str) -> MockResult<&mut Ship> {
+        let ship = self.orbiting_ship(symbol, SHIP_IN_TRANSIT_ERROR)?;
+        if ship.cooldown.remaining_seconds > 0 {
+            return Err(MockError::api(
+                409,
+                COOLDOWN_CONFLICT_ERROR,
+                format!("Ship {} is on cooldown", symbol),
+            )
+            .with_data(json!({ "cooldown": ship.cooldown })));
+        }
+        Ok(ship)
+    }
+
+    pub fn orbit(&mut self, symbol: &str) -> MockResult<Value> {
+        let ship = self.landed_ship(symbol, SHIP_IN_TRANSIT_ERROR)?;
+        ship.nav.status = ShipNavStatus::InOrbit;
+        Ok(json!({ "nav": ship.nav }))
+    }
+
+    pub fn dock(&mut self, symbol: &str) -> MockResult<Value> {
+        let ship = self.landed_ship(symbol, SHIP_IN_TRANSIT_ERROR)?;
+        ship.nav.status = ShipNavStatus::Docked;
+        Ok(json!({ "nav": ship.nav }))
+    }
+
+    pub fn set_flight_mode(
+        &mut self,
+        symbol: &str,
+        flight_mode: ShipNavFlightMode,
+    ) -> MockResult<Value> {
+        let ship = self.ship(symbol)?;
+        ship.nav.flight_mode = flight_mode;
+        Ok(json!(ship.nav))
+    }
+
+    pub fn navigate(&mut self, symbol: &str, destination: &str) -> MockResult<Value> {
+        let now = self.now();
+        let Some(target) = self.waypoint(destination).cloned() else {
+            return Err(MockError::not_found(format!(
+                "Waypoint {} not found",
+                destination
+            )));
+        };
+        let origin = {
+            let ship = self.ship(symbol)?;
+            ship.nav.waypoint_symbol.clone()
+        };
+        let origin = self.waypoint(&origin).unwrap().clone();
+        let ship = self.orbiting_ship(symbol, NAVIGATE_IN_TRANSIT_ERROR)?;
+        if origin.symbol == target.symbol {
+            return Err(MockError::api(
+                400,
+                NAVIGATE_SAME_DESTINATION_ERROR,
+                format!("Ship {} is already at {}", symbol, destination),
+            ));
+        }
+        let distance = routing::distance((origin.x, origin.y), (target.x, target.y));
+        let mode = ship.nav.flight_mode.clone();
+        let fuel = if ship.fuel.capacity == 0 {
+            0
+        } else {
+            routing::fuel_cost(distance, &mode)
+        };
+        if fuel > ship.fuel.current {
+            return Err(MockError::api(
+                400,
+                NAVIGATE_INSUFFICIENT_FUEL_ERROR,
+                format!("Ship {} has insufficient fuel", symbol),
+            )
+            .with_data(json!({
+                "fuelRequired": fuel,
+                "fuelAvailable": ship.fuel.current,
+            })));
+        }
+        let seconds = routing::travel_time(distance, &mode, ship.engine.speed.unwrap_or(1));
+        ship.fuel.current -= fuel;
+        ship.fuel.consumed = Some(crate::models::FuelConsumed {
+            amount: fuel,
+            timestamp: now,
+        });
+        ship.nav.route.origin = route_waypoint(&origin);
+        ship.nav.route.destination = route_waypoint(&target);
+        ship.nav.route.departure_time = now;
+        ship.nav.route.arrival = now + chrono::Duration::seconds(seconds as i64);
+        ship.nav.waypoint_symbol = target.symbol.clone();
+        ship.nav.status = ShipNavStatus::InTransit;
+        Ok(json!({ "fuel": ship.fuel, "nav": ship.nav }))
+    }
+
+    pub fn refuel(&mut self, symbol: &str) -> MockResult<Value> {
+        let now = self.now();
+        let ship = self.docked_ship(symbol)?;
+        let needed = ship.fuel.capacity - ship.fuel.current;
+        let waypoint = ship.nav.waypoint_symbol.clone();
+        let good = self.market_good(&waypoint, &TradeSymbol::Fuel)?;
+        let units = needed.div_ceil(FUEL_PER_MARKET_UNIT);
+        let price = good.purchase_price * units as u64;
+        if price > self.agent.credits {
+            return Err(MockError::api(
+                400,
+                MARKET_TRADE_INSUFFICIENT_CREDITS_ERROR,
+                "Agent has insufficient credits to refuel",
+            ));
+        }
+        self.agent.credits -= price;
+        let transaction = self.record_transaction(
+            &waypoint,
+            symbol,
+            &good,
+            MarketTransactionType::Purchase,
+            units,
+            now,
+        );
+        let ship = self.ship(symbol)?;
+        ship.fuel.current = ship.fuel.capacity;
+        Ok(json!({
+            "agent": self.agent,
+            "fuel": self.ships[symbol].fuel,
+            "transaction": transaction,
+        }))
+    }
+
+    pub fn cooldown(&mut self, symbol: &str) -> MockResult<Option<Cooldown>> {
+        let ship = self.ship(symbol)?;
+        Ok((ship.cooldown.remaining_seconds > 0).then(|| ship.cooldown.clone()))
+    }
+
+    fn start_cooldown(ship: &mut Ship, seconds: u64, now: DateTime<Utc>) -> Cooldown {
+        ship.cooldown = Cooldown {
+            ship_symbol: ship.symbol.clone(),
+            total_seconds: seconds,
+            remaining_seconds: seconds,
+            expiration: (seconds > 0).then(|| now + chrono::Duration::seconds(seconds as i64)),
+        };
+        ship.cooldown.clone()
+    }
+
+    pub fn extract(&mut self, symbol: &str, survey: Option<&Survey>) -> MockResult<Value> {
+        let now = self.now();
+        let seconds = self.config.extract_cooldown.as_secs();
+        let units = self.config.extraction_units;
+        let waypoint = self.ship(symbol)?.nav.waypoint_symbol.clone();
+        let deposits = match survey {
+            Some(survey) => self.use_survey(survey, &waypoint)?,
+            None => self
+                .world
+                .deposits
+                .get(&waypoint)
+                .cloned()
+                .unwrap_or_default(),
+        };
+        if deposits.is_empty() {
+            return Err(MockError::api(
+                400,
+                EXTRACT_INVALID_WAYPOINT_ERROR,
+                format!("Nothing can be extracted at {}", waypoint),
+            ));
+        }
+        let richness = self
+            .waypoint(&waypoint)
+            .map_or(1.0, |waypoint| world::richness(&waypoint.waypoint_type));
+        let (pick, variation) = match &mut self.rng {
+            Some(rng) => (rng.gen_range(0..deposits.len()), rng.gen_range(0.75..1.25)),
+            None => (self.extractions, 1.0),
+        };
+        let extracted = deposits[pick % deposits.len()].clone();
+        let units = ((units as f64 * richness * variation).round() as u32).max(1);
+        let ship = self.ready_ship(symbol)?;
+        if !has_mount(ship, world::MINING_LASER) {
+            return Err(MockError::api(
+                400,
+                SHIP_MISSING_MOUNT_ERROR,
+                format!("Ship {} has no mining laser", symbol),
+            ));
+        }
+        let free = ship.cargo.capacity - ship.cargo.units;
+        if free == 0 {
+            return Err(MockError::api(
+                400,
+                CARGO_EXCEEDS_LIMIT_ERROR,
+                format!("Ship {} has no room in its hold", symbol),
+            ));
+        }
+        let units = units.min(free);
+        add_cargo(ship, &extracted, units);
+        let cooldown = Self::start_cooldown(ship, seconds, now);
+        let cargo = ship.cargo.clone();
+        self.extractions += 1;
+        Ok(json!({
+            "extraction": {
+                "shipSymbol": symbol,
+                "yield": { "symbol": extracted, "units": units },
+            },
+            "cooldown": cooldown,
+            "cargo": cargo,
+        }))
+    }
+
+    /// Checks a survey sent back with an extraction and uses up one of its
+    /// extractions, returning the deposits to draw from.
+    fn use_survey(&mut self, survey: &Survey, waypoint: &str) -> MockResult<Vec<TradeSymbol>> {
+        let now = self.now();
+        let Some((known, remaining)) = self.surveys.get_mut(&survey.signature) else {
+            return Err(MockError::api(
+                400,
+                SHIP_SURVEY_VERIFICATION_ERROR,
+                format!("Survey {} is not recognised", survey.signature),
+            ));
+        };
+        if known.symbol != waypoint {
+            return Err(MockError::api(
+                400,
+                SHIP_SURVEY_VERIFICATION_ERROR,
+                format!("Survey {} is for {}", survey.signature, known.symbol),
+            ));
+        }
+        if known.is_expired(now) {
+            return Err(MockError::api(
+                400,
+                SHIP_SURVEY_EXPIRATION_ERROR,
+                format!("Survey {} has expired", survey.signature),
+            ));
+        }
+        if *remaining == 0 {
+            return Err(MockError::api(
+                400,
+                SHIP_SURVEY_EXHAUSTED_ERROR,
+                format!("Survey {} has been exhausted", survey.signature),
+            ));
+        }
+        *remaining -= 1;
+        Ok(known
+            .deposits
+            .iter()
+            .map(|deposit| deposit.symbol.clone())
+            .collect())
+    }
+
+    pub fn survey(&mut self, symbol: &str) -> MockResult<Value> {
+        let now = self.now();
+        let seconds = self.config.survey_cooldown.as_secs();
+        let lifetime = chrono::Duration::from_std(self.config.survey_lifetime).unwrap_or_default();
+        let waypoint = self.ship(symbol)?.nav.waypoint_symbol.clone();
+        let Some(deposits) = self.world.deposits.get(&waypoint).cloned() else {
+            return Err(MockError::api(
+                400,
+                EXTRACT_INVALID_WAYPOINT_ERROR,
+                format!("Nothing can be surveyed at {}", waypoint),
+            ));
+        };
+        let ship = self.ready_ship(symbol)?;
+        if !has_mount(ship, world::SURVEYOR) {
+            return Err(MockError::api(
+                400,
+                SHIP_MISSING_MOUNT_ERROR,
+                format!("Ship {} has no surveyor", symbol),
+            ));
+        }
+        let cooldown = Self::start_cooldown(ship, seconds, now);
+        let sizes = [SurveySize::Small, SurveySize::Moderate, SurveySize::Large];
+        let mut surveys = Vec::new();
+        for _ in 0..2 {
+            let n = self.surveys_made;
+            self.surveys_made += 1;
+            let size = sizes[n % sizes.len()].clone();
+            let survey = Survey {
+                signature: format!("{}-{:05}", waypoint, n),
+                symbol: waypoint.clone(),
+                deposits: (0..4)
+                    .map(|i| SurveyDeposit {
+                        symbol: deposits[(n + i * (n % 3 + 1)) % deposits.len()].clone(),
+                    })
+                    .collect(),
+                expiration: now + lifetime,
+                size: size.clone(),
+            };
+            let uses = match size {
+                SurveySize::Small => 5,
+                SurveySize::Moderate => 10,
+                _ => 20,
+            };
+            self.surveys
+                .insert(survey.signature.clone(), (survey.clone(), uses));
+            surveys.push(survey);
+        }
+        Ok(json!({ "cooldown": cooldown, "surveys": surveys }))
+    }
+
+    pub fn cargo(&mut self, symbol: &str) -> MockResult<Value> {
+        Ok(json!(self.ship(symbol)?.cargo))
+    }
+
+    pub fn jettison(&mut self, symbol: &str, good: &TradeSymbol, units: u32) -> MockResult<Value> {
+        let ship = self.landed_ship(symbol, SHIP_IN_TRANSIT_ERROR)?;
+        remove_cargo(ship, good, units)?;
+        Ok(json!({ "cargo": ship.cargo }))
+    }
+
+    pub fn sell(&mut self, symbol: &str, good: &TradeSymbol, units: u32) -> MockResult<Value> {
+        self.trade(symbol, good, units, MarketTransactionType::Sell)
+    }
+
+    pub fn purchase(&mut self, symbol: &str, good: &TradeSymbol, units: u32) -> MockResult<Value> {
+        self.trade(symbol, good, units, MarketTransactionType::Purchase)
+    }
+
+    fn trade(
+        &mut self,
+        symbol: &str,
+        good: &TradeSymbol,
+        units: u32,
+        kind: MarketTransactionType,
+    ) -> MockResult<Value> {
+        let now = self.now();
+        let waypoint = self.docked_ship(symbol)?.nav.waypoint_symbol.clone();
+        let listing = self.market_good(&waypoint, good)?;
+        if units > listing.trade_volume {
+            return Err(MockError::api(
+                400,
+                MARKET_TRADE_UNIT_LIMIT_ERROR,
+                format!(
+                    "{} trades at most {} units of {} at a time",
+                    waypoint, listing.trade_volume, good
+                ),
+            ));
+        }
+        let ship = self.ships.get_mut(symbol).unwrap();
+        if kind == MarketTransactionType::Sell {
+            remove_cargo(ship, good, units)?;
+            self.agent.credits += listing.sell_price * units as u64;
+        } else {
+            let price = listing.purchase_price * units as u64;
+            if price > self.agent.credits {
+                return Err(MockError::api(
+                    400,
+                    MARKET_TRADE_INSUFFICIENT_CREDITS_ERROR,
+                    format!("Agent has insufficient credits to buy {} {}", units, good),
+                ));
+            }
+            if ship.cargo.units + units > ship.cargo.capacity {
+                return Err(MockError::api(
+                    400,
+                    CARGO_EXCEEDS_LIMIT_ERROR,
+                    format!("Ship {} has no room for {} {}", symbol, units, good),
+                ));
+            }
+            add_cargo(ship, good, units);
+            self.agent.credits -= price;
+        }
+        let transaction = self.record_transaction(&waypoint, symbol, &listing, kind, units, now);
+        Ok(json!({
+            "agent": self.agent,
+            "cargo": self.ships[symbol].cargo,
+            "transaction": transaction,
+        }))
+    }
+
+    fn market_good(&self, waypoint: &str, good: &TradeSymbol) -> MockResult<MarketTradeGood> {
+        self.world
+            .markets
+            .get(waypoint)
+            .and_then(|market| market.trade_good(good))
+            .map(|listing| self.priced(waypoint, listing))
+            .ok_or_else(|| {
+                MockError::api(
+                    400,
+                    MARKET_NOT_TRADED_ERROR,
+                    format!("{} is not traded at {}", good, waypoint),
+                )
+            })
+    }
+
+    /// How far trades have pushed `good` at `waypoint`, after the market
+    /// has had time to recover.
+    fn pressure(&self, waypoint: &str, good: &TradeSymbol) -> f64 {
+        let Some(pressure) = self.pressure.get(&(waypoint.to_string(), good.clone())) else {
+            return 0.0;
+        };
+        let half_life = self.config.market_recovery.as_secs_f64().max(1.0);
+        let elapsed = (self.now() - pressure.at).num_seconds().max(0) as f64;
+        pressure.level * 0.5f64.powf(elapsed / half_life)
+    }
+
+    /// A listing with its prices and supply moved by our past trades: every
+    /// trade volume sold lowers both prices by about `market_elasticity`,
+    /// every trade volume bought raises them.
+    fn priced(&self, waypoint: &str, listing: &MarketTradeGood) -> MarketTradeGood {
+        let pressure = self.pressure(waypoint, &listing.symbol);
+        let factor = (-self.config.market_elasticity * pressure).exp();
+        let scale = |price: u64| ((price as f64 * factor).round() as u64).max(1);
+        MarketTradeGood {
+            purchase_price: scale(listing.purchase_price),
+            sell_price: scale(listing.sell_price),
+            supply: match pressure {
+                p if p <= -2.0 => SupplyLevel::Scarce,
+                p if p < -0.5 => SupplyLevel::Limited,
+                p if p < 0.5 => listing.supply.clone(),
+                p if p < 2.0 => SupplyLevel::High,
+                _ => SupplyLevel::Abundant,
+            },
+            ..listing.clone()
+        }
+    }
+
+    fn record_transaction(
+        &mut self,
+        waypoint: &str,
+        ship_symbol: &str,
+        good: &MarketTradeGood,
+        kind: MarketTransactionType,
+        units: u32,
+        now: DateTime<Utc>,
+    ) -> MarketTransaction {
+        let price_per_unit = match kind {
+            MarketTransactionType::Sell => good.sell_price,
+            _ => good.purchase_price,
+        };
+        let transaction = MarketTransaction {
+            waypoint_symbol: waypoint.to_string(),
+            ship_symbol: ship_symbol.to_string(),
+            trade_symbol: good.symbol.clone(),
+            transaction_type: kind,
+            units,
+            price_per_unit,
+            total_price: price_per_unit * units as u64,
+            timestamp: now,
+        };
+        let volume = good.trade_volume.max(1) as f64;
+        let push = match transaction.transaction_type {
+            MarketTransactionType::Sell => units as f64 / volume,
+            _ => -(units as f64) / volume,
+        };
+        let level = self.pressure(waypoint, &good.symbol) + push;
+        self.pressure.insert(
+            (waypoint.to_string(), good.symbol.clone()),
+            Pressure { level, at: now },
+        );
+        let market = self.world.markets.get_mut(waypoint).unwrap();
+        market.transactions.push(transaction.clone());
+        if market.transactions.len() > MARKET_TRANSACTIONS {
+            market.transactions.remove(0);
+        }
+        transaction
+    }
+
+    pub fn system(&self, symbol: &str) -> MockResult<System> {
+        if symbol != self.world.system {
+            return Err(MockError::not_found(format!("System {} not found", symbol)));
+        }
+        Ok(System {
+            symbol: symbol.to_string(),
+            sector_symbol: symbol.split('-').next().unwrap_or_default().to_string(),
+            system_type: "RED_STAR".to_string(),
+            x: 0,
+            y: 0,
+            waypoints: self
+                .world
+                .waypoints
+                .iter()
+                .map(|waypoint| SystemWaypoint {
+                    symbol: waypoint.symbol.clone(),
+                    waypoint_type: waypoint.waypoint_type.clone(),
+                    x: waypoint.x,
+                    y: waypoint.y,
+                    orbitals: waypoint.orbitals.clone(),
+                    orbits: waypoint.orbits.clone(),
+                })
+                .collect(),
+            factions: Vec::new(),
+        })
+    }
+
+    pub fn waypoint(&self, symbol: &str) -> Option<&Waypoint> {
+        self.world.waypoint(symbol)
+    }
+
+    pub fn waypoints(&self, system: &str) -> MockResult<Vec<Waypoint>> {
+        self.system(system)?;
+        Ok(self.world.waypoints.clone())
+    }
+
+    /// Whether one of our ships is at `waypoint` (not just passing through),
+    /// which is what unlocks prices at markets and shipyards.
+    fn has_ship_at(&mut self, waypoint: &str) -> bool {
+        self.settle();
+        self.ships.values().any(|ship| {
+            ship.nav.waypoint_symbol == waypoint && ship.nav.status != ShipNavStatus::InTransit
+        })
+    }
+
+    pub fn market(&mut self, waypoint: &str) -> MockResult<Market> {
+        let present = self.has_ship_at(waypoint);
+        let mut market = self
+            .world
+            .markets
+            .get(waypoint)
+            .cloned()
+            .ok_or_else(|| MockError::not_found(format!("No marketplace at {}", waypoint)))?;
+        if present {
+            market.trade_goods = market
+                .trade_goods
+                .iter()
+                .map(|listing| self.priced(waypoint, listing))
+                .collect();
+        } else {
+            market.trade_goods.clear();
+            market.transactions.clear();
+        }
+        Ok(market)
+    }
+
+    pub fn shipyard(&mut self, waypoint: &str) -> MockResult<AvailableShips> {
+        let present = self.has_ship_at(waypoint);
+        let hulls = self
+            .world
+            .shipyards
+            .get(waypoint)
+            .ok_or_else(|| MockError::not_found(format!("No shipyard at {}", waypoint)))?;
+        Ok(AvailableShips {
+            symbol: Some(waypoint.to_string()),
+            ship_types: Some(
+                hulls
+                    .iter()
+                    .map(|(ship_type, _)| ShipyardShipType {
+                        ship_type: Some(ship_type.clone()),
+                    })
+                    .collect(),
+            ),
+            transactions: None,
+            ships: present.then(|| {
+                hulls
+                    .iter()
+                    .map(|(ship_type, price)| world::shipyard_ship(ship_type, *price))
+                    .collect()
+            }),
+            modifications_fee: Some(0),
+        })
+    }
+
+    pub fn buy_ship(&mut self, ship_type: &ShipType, waypoint: &str) -> MockResult<Value> {
+        let price = self
+            .world
+            .shipyards
+            .get(waypoint)
+            .and_then(|hulls| hulls.iter().find(|(hull, _)| hull == ship_type))
+            .map(|(_, price)| *price)
+            .ok_or_else(|| {
+                MockError::api(
+                    400,
+                    SHIPYARD_NOT_SELLING_ERROR,
+                    format!("{} does not sell {}", waypoint, ship_type),
+                )
+            })?;
+        if !self.has_ship_at(waypoint) {
+            return Err(MockError::invalid(format!(
+                "A ship must be present at {} to buy from it",
+                waypoint
+            )));
+        }
+        if price > self.agent.credits {
+            return Err(MockError::api(
+                400,
+                PURCHASE_SHIP_CREDITS_ERROR,
+                format!("Agent has insufficient credits to buy {}", ship_type),
+            ));
+        }
+        self.agent.credits -= price;
+        let location = self.waypoint(waypoint).unwrap().clone();
+        let ship = self.add_ship(ship_type, &location);
+        let transaction = Transaction {
+            ship_symbol: Some(ship.symbol.clone()),
+            ship_type: Some(ship_type.clone()),
+            waypoint_symbol: Some(waypoint.to_string()),
+            price: Some(price),
+            timestamp: Some(self.now().to_rfc3339()),
+        };
+        Ok(json!({ "agent": self.agent, "ship": ship, "transaction": transaction }))
+    }
+}
+
+fn route_waypoint(waypoint: &Waypoint) -> ShipNavRouteWaypoint {
+    ShipNavRouteWaypoint {
+        symbol: waypoint.symbol.clone(),
+        waypoint_type: waypoint.waypoint_type.clone(),
+        system_symbol: waypoint.system_symbol.clone(),
+        x: waypoint.x,
+        y: waypoint.y,
+    }
+}
+
+fn has_mount(ship: &Ship, mount: &str) -> bool {
+    ship.mounts
+        .iter()
+        .any(|installed| installed.symbol.as_deref() == Some(mount))
+}
+
+fn add_cargo(ship: &mut Ship, good: &TradeSymbol, units: u32) {
+    ship.cargo.units += units;
+    match ship
+        .cargo
+        .inventory
+        .iter_mut()
+        .find(|item| &item.symbol == good)
+    {
+        Some(item) => item.units += units,
+        None => ship.cargo.inventory.push(CargoObject {
+            description: String::new(),
+            name: good.to_string(),
+            symbol: good.clone(),
+            units,
+        }),
+    }
+}
+
+fn remove_cargo(ship: &mut Ship, good: &TradeSymbol, units: u32) -> MockResult<()> {
+    let cargo: &mut Cargo = &mut ship.cargo;
+    let Some(index) = cargo
+        .inventory
+        .iter()
+        .position(|item| &item.symbol == good && item.units >= units)
+    else {
+        return Err(MockError::api(
+            400,
+            CARGO_MISSING_ERROR,
+            format!("Ship {} does not hold {} {}", ship.symbol, units, good),
+        ));
+    };
+    cargo.units -= units;
+    cargo.inventory[index].units -= units;
+    if cargo.inventory[index].units == 0 {
+        cargo.inventory.remove(index);
+    }
+    Ok(())
+}
@@ -0,0 +1,679 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde_json::json;
+
+use crate::models::{
+    ActivityLevel, Market, MarketTradeGood, MarketTradeGoodType, Ship, ShipNavFlightMode,
+    ShipNavStatus, ShipRole, ShipType, ShipyardShip, SupplyLevel, TradeGood, TradeSymbol, Waypoint,
+    WaypointTraitSymbol, WaypointType,
+};
+
+/// Everything about the mock system that isn't owned by the agent.
+#[derive(Debug, Clone)]
+pub struct World {
+    pub system: String,
+    pub waypoints: Vec<Waypoint>,
+    /// Full market state, prices included, keyed by waypoint.
+    pub markets: BTreeMap<String, Market>,
+    /// Hulls for sale and their price, keyed by waypoint.
+    pub shipyards: BTreeMap<String, Vec<(ShipType, u64)>>,
+    /// What extracting at a waypoint can yield, in the order yields cycle.
+    pub deposits: BTreeMap<String, Vec<TradeSymbol>>,
+}
+
+impl World {
+    pub fn waypoint(&self, symbol: &str) -> Option<&Waypoint> {
+        self.waypoints
+            .iter()
+            .find(|waypoint| waypoint.symbol == symbol)
+    }
+
+    pub fn headquarters(&self) -> String {
+        format!("{}-A1", self.system)
+    }
+
+    /// Goods a procurement contract can ask for: every good a market imports
+    /// that can be mined in the system, with where it is wanted and the
+    /// price it fetches there.
+    pub fn contract_goods(&self) -> Vec<(TradeSymbol, String, u64)> {
+        let minable: Vec<&TradeSymbol> = self.deposits.values().flatten().collect();
+        self.markets
+            .values()
+            .flat_map(|market| {
+                market
+                    .trade_goods
+                    .iter()
+                    .filter(|good| {
+                        good.trade_type == MarketTradeGoodType::Import
+                            && minable.contains(&&good.symbol)
+                    })
+                    .map(|good| (good.symbol.clone(), market.symbol.clone(), good.sell_price))
+            })
+            .collect()
+    }
+}
+
+/// How much one extraction yields at a waypoint of this type, relative to
+/// an engineered asteroid.
+pub fn richness(waypoint_type: &WaypointType) -> f64 {
+    match waypoint_type {
+        WaypointType::Asteroid => 0.8,
+        WaypointType::AsteroidField => 0.6,
+        _ => 1.0,
+    }
+}
+
+/// What can be extracted at an asteroid of this type with these traits. A
+/// good listed twice comes up twice as often.
+pub fn deposits_for(
+    waypoint_type: &WaypointType,
+    traits: &[WaypointTraitSymbol],
+) -> Vec<TradeSymbol> {
+    use TradeSymbol::*;
+    match waypoint_type {
+        WaypointType::EngineeredAsteroid => vec![
+            IronOre,
+            IronOre,
+            CopperOre,
+            AluminumOre,
+            QuartzSand,
+            SiliconCrystals,
+        ],
+        WaypointType::AsteroidField => vec![IceWater, IceWater, AmmoniaIce, QuartzSand],
+        WaypointType::Asteroid => traits
+            .iter()
+            .flat_map(WaypointTraitSymbol::deposits)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The fixed layout every mock server starts from: a headquarters planet
+/// with a moon, two asteroids, an orbital station and a distant planet, four
+/// of them with marketplaces and two with shipyards.
+pub fn build(system: &str) -> World {
+    let symbol = |suffix: &str| format!("{}-{}", system, suffix);
+    let waypoints = vec![
+        waypoint(
+            system,
+            "A1",
+            WaypointType::Planet,
+            (0, 0),
+            &[
+                WaypointTraitSymbol::Marketplace,
+                WaypointTraitSymbol::Shipyard,
+            ],
+        ),
+        waypoint(
+            system,
+            "A2",
+            WaypointType::Moon,
+            (0, 0),
+            &[WaypointTraitSymbol::Marketplace],
+        ),
+        waypoint(
+            system,
+            "B1",
+            WaypointType::EngineeredAsteroid,
+            (20, -15),
+            &[WaypointTraitSymbol::CommonMetalDeposits],
+        ),
+        waypoint(
+            system,
+            "B2",
+            WaypointType::Asteroid,
+            (45, 30),
+            &[
+                WaypointTraitSymbol::MineralDeposits,
+                WaypointTraitSymbol::PreciousMetalDeposits,
+            ],
+        ),
+        waypoint(
+            system,
+            "C1",
+            WaypointType::OrbitalStation,
+            (-30, 40),
+            &[WaypointTraitSymbol::Marketplace],
+        ),
+        waypoint(system, "D1", WaypointType::GasGiant, (80, 10), &[]),
+        waypoint(
+            system,
+            "E1",
+            WaypointType::Planet,
+            (-70, -50),
+            &[
+                WaypointTraitSymbol::Marketplace,
+                WaypointTraitSymbol::Shipyard,
+            ],
+        ),
+    ];
+
+    use MarketTradeGoodType::{Exchange, Export, Import};
+    use TradeSymbol::*;
+    let markets = BTreeMap::from([
+        (
+            symbol("A1"),
+            market(
+                &symbol("A1"),
+                &[
+                    (Fuel, Exchange, 72),
+                    (IronOre, Import, 46),
+                    (CopperOre, Import, 52),
+                    (AluminumOre, Import, 58),
+                    (Machinery, Export, 120),
+                    (ShipParts, Export, 310),
+                ],
+            ),
+        ),
+        (
+            symbol("A2"),
+            market(
+                &symbol("A2"),
+                &[
+                    (Fuel, Exchange, 70),
+                    (QuartzSand, Import, 24),
+                    (SiliconCrystals, Import, 38),
+                    (IceWater, Import, 18),
+                    (Food, Export, 60),
+                ],
+            ),
+        ),
+        (
+            symbol("C1"),
+            market(
+                &symbol("C1"),
+                &[
+                    (Fuel, Exchange, 78),
+                    (IronOre, Import, 55),
+                    (PreciousStones, Import, 96),
+                    (Machinery, Import, 185),
+                    (Food, Import, 88),
+                ],
+            ),
+        ),
+        (
+            symbol("E1"),
+            market(
+                &symbol("E1"),
+                &[
+                    (Fuel, Exchange, 66),
+                    (CopperOre, Import, 64),
+                    (AluminumOre, Import, 71),
+                    (ShipParts, Import, 420),
+                    (Food, Export, 52),
+                ],
+            ),
+        ),
+    ]);
+
+    let shipyards = BTreeMap::from([
+        (
+            symbol("A1"),
+            vec![
+                (ShipType::ShipProbe, 25_000),
+                (ShipType::ShipMiningDrone, 48_000),
+                (ShipType::ShipSurveyor, 36_000),
+            ],
+        ),
+        (
+            symbol("E1"),
+            vec![
+                (ShipType::ShipMiningDrone, 44_000),
+                (ShipType::ShipLightHauler, 120_000),
+            ],
+        ),
+    ]);
+
+    let deposits = BTreeMap::from([
+        (
+            symbol("B1"),
+            vec![
+                IronOre,
+                QuartzSand,
+                CopperOre,
+                IronOre,
+                SiliconCrystals,
+                AluminumOre,
+                IceWater,
+            ],
+        ),
+        (
+            symbol("B2"),
+            vec![PreciousStones, IronOre, QuartzSand, AluminumOre],
+        ),
+    ]);
+
+    World {
+        system: system.to_string(),
+        waypoints,
+        markets,
+        shipyards,
+        deposits,
+    }
+}
+
+/// Reference prices for goods generated markets trade: raw goods first,
+/// then manufactured ones.
+const RAW_GOODS: [(TradeSymbol, u64); 12] = [
+    (TradeSymbol::IronOre, 48),
+    (TradeSymbol::CopperOre, 55),
+    (TradeSymbol::AluminumOre, 60),
+    (TradeSymbol::QuartzSand, 22),
+    (TradeSymbol::SiliconCrystals, 36),
+    (TradeSymbol::IceWater, 16),
+    (TradeSymbol::AmmoniaIce, 28),
+    (TradeSymbol::PreciousStones, 90),
+    (TradeSymbol::SilverOre, 110),
+    (TradeSymbol::GoldOre, 140),
+    (TradeSymbol::PlatinumOre, 180),
+    (TradeSymbol::UraniteOre, 220),
+];
+const MANUFACTURED_GOODS: [(TradeSymbol, u64); 8] = [
+    (TradeSymbol::Machinery, 130),
+    (TradeSymbol::Food, 60),
+    (TradeSymbol::Fabrics, 75),
+    (TradeSymbol::Electronics, 170),
+    (TradeSymbol::Equipment, 150),
+    (TradeSymbol::Fertilizers, 55),
+    (TradeSymbol::Plastics, 70),
+    (TradeSymbol::ShipParts, 300),
+];
+
+/// A random system with the same shape as the fixed layout: headquarters
+/// with a moon at the centre, three asteroids, three more marketplaces and a
+/// gas giant. Every minable good is imported somewhere and every
+/// manufactured export is wanted at another market, so both mining and
+/// trading can pay. The same seed always builds the same system.
+pub fn generate(system: &str, seed: u64) -> World {
+    use MarketTradeGoodType::{Exchange, Export, Import};
+    let mut rng = StdRng::seed_from_u64(seed);
+    let market_traits = [WaypointTraitSymbol::Marketplace];
+    let shipyard_traits = [
+        WaypointTraitSymbol::Marketplace,
+        WaypointTraitSymbol::Shipyard,
+    ];
+    let mut waypoints = vec![
+        waypoint(system, "A1", WaypointType::Planet, (0, 0), &shipyard_traits),
+        waypoint(system, "A2", WaypointType::Moon, (0, 0), &market_traits),
+        waypoint(
+            system,
+            "B1",
+            WaypointType::EngineeredAsteroid,
+            position(&mut rng, 15.0, 35.0),
+            &[],
+        ),
+    ];
+    let asteroid_kinds = [
+        (
+            WaypointType::Asteroid,
+            vec![
+                WaypointTraitSymbol::CommonMetalDeposits,
+                WaypointTraitSymbol::MineralDeposits,
+            ],
+        ),
+        (
+            WaypointType::Asteroid,
+            vec![WaypointTraitSymbol::PreciousMetalDeposits],
+        ),
+        (
+            WaypointType::Asteroid,
+            vec![
+                WaypointTraitSymbol::RareMetalDeposits,
+                WaypointTraitSymbol::CommonMetalDeposits,
+            ],
+        ),
+        (WaypointType::AsteroidField, Vec::new()),
+    ];
+    for suffix in ["B2", "B3"] {
+        let (waypoint_type, traits) = &asteroid_kinds[rng.gen_range(0..asteroid_kinds.len())];
+        let at = position(&mut rng, 40.0, 90.0);
+        waypoints.push(waypoint(system, suffix, waypoint_type.clone(), at, traits));
+    }
+    let stations = [
+        ("C1", WaypointType::OrbitalStation, &market_traits[..]),
+        ("C2", WaypointType::Planet, &shipyard_traits[..]),
+        ("C3", WaypointType::Moon, &market_traits[..]),
+    ];
+    for (suffix, waypoint_type, traits) in stations {
+        let at = position(&mut rng, 30.0, 110.0);
+        waypoints.push(waypoint(system, suffix, waypoint_type, at, traits));
+    }
+    let at = position(&mut rng, 80.0, 120.0);
+    waypoints.push(waypoint(system, "D1", WaypointType::GasGiant, at, &[]));
+
+    let deposits: BTreeMap<String, Vec<TradeSymbol>> = waypoints
+        .iter()
+        .map(|waypoint| {
+            let traits: Vec<_> = waypoint.traits.iter().map(|t| t.symbol.clone()).collect();
+            (
+                waypoint.symbol.clone(),
+                deposits_for(&waypoint.waypoint_type, &traits),
+            )
+        })
+        .filter(|(_, deposits)| !deposits.is_empty())
+        .collect();
+
+    // Spread the minable goods over the marketplaces, then give each one a
+    // couple of exports and something another market makes.
+    let marketplaces: Vec<String> = waypoints
+        .iter()
+        .filter(|waypoint| waypoint.has_trait(&WaypointTraitSymbol::Marketplace))
+        .map(|waypoint| waypoint.symbol.clone())
+        .collect();
+    let mut goods: BTreeMap<&str, Vec<(TradeSymbol, MarketTradeGoodType, u64)>> = marketplaces
+        .iter()
+        .map(|symbol| {
+            let fuel = 64 + rng.gen_range(0..16);
+            (symbol.as_str(), vec![(TradeSymbol::Fuel, Exchange, fuel)])
+        })
+        .collect();
+    let mut minable: Vec<&TradeSymbol> = deposits.values().flatten().collect();
+    minable.sort();
+    minable.dedup();
+    for (i, (symbol, base)) in RAW_GOODS
+        .iter()
+        .filter(|(symbol, _)| minable.contains(&symbol))
+        .enumerate()
+    {
+        for market in [i, i + 2] {
+            let market = &marketplaces[market % marketplaces.len()];
+            let price = (*base as f64 * rng.gen_range(1.0..1.4)).round() as u64;
+            let listed = goods.get_mut(market.as_str()).unwrap();
+            if !listed.iter().any(|(listed, _, _)| listed == symbol) {
+                listed.push((symbol.clone(), Import, price));
+            }
+        }
+    }
+    for (i, market) in marketplaces.iter().enumerate() {
+        for j in 0..2 {
+            let (symbol, base) = &MANUFACTURED_GOODS[(2 * i + j) % MANUFACTURED_GOODS.len()];
+            let price = (*base as f64 * rng.gen_range(0.7..0.95)).round() as u64;
+            goods
+                .get_mut(market.as_str())
+                .unwrap()
+                .push((symbol.clone(), Export, price));
+            let buyer = &marketplaces
+                [(i + 1 + rng.gen_range(0..marketplaces.len() - 1)) % marketplaces.len()];
+            let price = (*base as f64 * rng.gen_range(1.1..1.5)).round() as u64;
+            let listed = goods.get_mut(buyer.as_str()).unwrap();
+            if !listed.iter().any(|(listed, _, _)| listed == symbol) {
+                listed.push((symbol.clone(), Import, price));
+            }
+        }
+    }
+    let markets = goods
+        .into_iter()
+        .map(|(symbol, goods)| (symbol.to_string(), market(symbol, &goods)))
+        .collect();
+
+    let shipyards = BTreeMap::from([
+        (
+            format!("{}-A1", system),
+            vec![
+                (ShipType::ShipProbe, 25_000),
+                (ShipType::ShipMiningDrone, 48_000),
+                (ShipType::ShipSurveyor, 36_000),
+                (ShipType::ShipLightHauler, 125_000),
+            ],
+        ),
+        (
+            format!("{}-C2", system),
+            vec![
+                (ShipType::ShipMiningDrone, 45_000),
+                (ShipType::ShipLightHauler, 118_000),
+            ],
+        ),
+    ]);
+
+    World {
+        system: system.to_string(),
+        waypoints,
+        markets,
+        shipyards,
+        deposits,
+    }
+}
+
+/// A random point between `min` and `max` from the system's centre.
+fn position(rng: &mut StdRng, min: f64, max: f64) -> (i32, i32) {
+    let angle = rng.gen_range(0.0..std::f64::consts::TAU);
+    let radius = rng.gen_range(min..max);
+    (
+        (radius * angle.cos()).round() as i32,
+        (radius * angle.sin()).round() as i32,
+    )
+}
+
+fn waypoint(
+    system: &str,
+    suffix: &str,
+    waypoint_type: WaypointType,
+    (x, y): (i32, i32),
+    traits: &[WaypointTraitSymbol],
+) -> Waypoint {
+    let traits: Vec<_> = traits
+        .iter()
+        .map(|symbol| {
+            json!({
+                "symbol": symbol,
+                "name": title_case(symbol.as_str()),
+                "description": "",
+            })
+        })
+        .collect();
+    serde_json::from_value(json!({
+        "symbol": format!("{}-{}", system, suffix),
+        "systemSymbol": system,
+        "type": waypoint_type,
+        "x": x,
+        "y": y,
+        "traits": traits,
+    }))
+    .expect("mock waypoint matches the model")
+}
+
+fn market(symbol: &str, goods: &[(TradeSymbol, MarketTradeGoodType, u64)]) -> Market {
+    let listed = |kind: &MarketTradeGoodType| -> Vec<TradeGood> {
+        goods
+            .iter()
+            .filter(|(_, trade_type, _)| trade_type == kind)
+            .map(|(symbol, _, _)| TradeGood {
+                symbol: symbol.clone(),
+                name: title_case(symbol.as_str()),
+                description: String::new(),
+            })
+            .collect()
+    };
+    Market {
+        symbol: symbol.to_string(),
+        exports: listed(&MarketTradeGoodType::Export),
+        imports: listed(&MarketTradeGoodType::Import),
+        exchange: listed(&MarketTradeGoodType::Exchange),
+        transactions: Vec::new(),
+        trade_goods: goods
+            .iter()
+            .map(|(symbol, trade_type, price)| MarketTradeGood {
+                symbol: symbol.clone(),
+                trade_type: trade_type.clone(),
+                trade_volume: 60,
+                supply: SupplyLevel::Moderate,
+                activity: Some(ActivityLevel::Growing),
+                // The market buys below and sells above the reference price.
+                purchase_price: price + price / 20 + 1,
+                sell_price: price - price / 20,
+            })
+            .collect(),
+    }
+}
+
+fn title_case(symbol: &str) -> String {
+    symbol
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_string() + &chars.as_str().to_lowercase(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The stats of one hull model.
+#[derive(Debug, Clone, Copy)]
+pub struct Hull {
+    pub role: &'static str,
+    pub fuel_capacity: u32,
+    pub speed: u32,
+    pub cargo_capacity: u32,
+    pub mounts: &'static [(&'static str, u32)],
+}
+
+pub const MINING_LASER: &str = "MOUNT_MINING_LASER_I";
+pub const SURVEYOR: &str = "MOUNT_SURVEYOR_I";
+
+pub fn hull(ship_type: &ShipType) -> Hull {
+    match ship_type {
+        ShipType::ShipCommandFrigate => Hull {
+            role: ShipRole::Command.as_str(),
+            fuel_capacity: 400,
+            speed: 30,
+            cargo_capacity: 40,
+            mounts: &[(MINING_LASER, 10), (SURVEYOR, 1)],
+        },
+        ShipType::ShipProbe => Hull {
+            role: ShipRole::Satellite.as_str(),
+            fuel_capacity: 0,
+            speed: 3,
+            cargo_capacity: 0,
+            mounts: &[],
+        },
+        ShipType::ShipMiningDrone => Hull {
+            role: ShipRole::Excavator.as_str(),
+            fuel_capacity: 80,
+            speed: 10,
+            cargo_capacity: 15,
+            mounts: &[(MINING_LASER, 10)],
+        },
+        ShipType::ShipSurveyor => Hull {
+            role: ShipRole::Surveyor.as_str(),
+            fuel_capacity: 80,
+            speed: 10,
+            cargo_capacity: 0,
+            mounts: &[(SURVEYOR, 1)],
+        },
+        _ => Hull {
+            role: ShipRole::Transport.as_str(),
+            fuel_capacity: 600,
+            speed: 15,
+            cargo_capacity: 80,
+            mounts: &[],
+        },
+    }
+}
+
+fn components(ship_type: &ShipType) -> serde_json::Value {
+    let hull = hull(ship_type);
+    let mounts: Vec<_> = hull
+        .mounts
+        .iter()
+        .map(|(symbol, strength)| {
+            json!({
+                "symbol": symbol,
+                "name": title_case(symbol),
+                "strength": strength,
+                "requirements": {},
+            })
+        })
+        .collect();
+    let modules: Vec<_> = (hull.cargo_capacity > 0)
+        .then(|| {
+            json!({
+                "symbol": "MODULE_CARGO_HOLD_I",
+                "name": "Cargo Hold",
+                "capacity": hull.cargo_capacity,
+                "requirements": {},
+            })
+        })
+        .into_iter()
+        .collect();
+    json!({
+        "frame": {
+            "symbol": format!("FRAME_{}", ship_type.as_str().trim_start_matches("SHIP_")),
+            "fuelCapacity": hull.fuel_capacity,
+            "requirements": {},
+            "condition": 1.0,
+            "integrity": 1.0,
+        },
+        "reactor": { "symbol": "REACTOR_FISSION_I", "requirements": {} },
+        "engine": { "symbol": "ENGINE_IMPULSE_DRIVE_I", "speed": hull.speed, "requirements": {} },
+        "modules": modules,
+        "mounts": mounts,
+    })
+}
+
+/// A freshly built ship of `ship_type`, docked at `location` with a full tank.
+pub fn ship(
+    ship_type: &ShipType,
+    symbol: &str,
+    faction: &str,
+    location: &Waypoint,
+    now: DateTime<Utc>,
+) -> Ship {
+    let hull = hull(ship_type);
+    let route_waypoint = json!({
+        "symbol": location.symbol,
+        "type": location.waypoint_type,
+        "systemSymbol": location.system_symbol,
+        "x": location.x,
+        "y": location.y,
+    });
+    let mut ship = components(ship_type);
+    let fields = json!({
+        "symbol": symbol,
+        "registration": { "name": symbol, "factionSymbol": faction, "role": hull.role },
+        "nav": {
+            "systemSymbol": location.system_symbol,
+            "waypointSymbol": location.symbol,
+            "route": {
+                "origin": route_waypoint,
+                "destination": route_waypoint,
+                "departureTime": now,
+                "arrival": now,
+            },
+            "status": ShipNavStatus::Docked,
+            "flightMode": ShipNavFlightMode::Cruise,
+        },
+        "crew": {
+            "current": 0, "required": 0, "capacity": 0,
+            "rotation": "STRICT", "morale": 100, "wages": 0,
+        },
+        "cooldown": { "shipSymbol": symbol, "totalSeconds": 0, "remainingSeconds": 0 },
+        "cargo": { "capacity": hull.cargo_capacity, "units": 0, "inventory": [] },
+        "fuel": { "current": hull.fuel_capacity, "capacity": hull.fuel_capacity },
+    });
+    ship.as_object_mut()
+        .unwrap()
+        .extend(fields.as_object().unwrap().clone());
+    serde_json::from_value(ship).expect("mock ship matches the model")
+}
+
+/// How a hull is listed at a shipyard while one of our ships is there.
+pub fn shipyard_ship(ship_type: &ShipType, price: u64) -> ShipyardShip {
+    let mut listing = components(ship_type);
+    let fields = json!({
+        "type": ship_type,
+        "name": title_case(ship_type.as_str()),
+        "supply": SupplyLevel::Moderate,
+        "purchasePrice": price,
+        "crew": { "required": 0, "capacity": 0 },
+    });
+    listing
+        .as_object_mut()
+        .unwrap()
+        .extend(fields.as_object().unwrap().clone());
+    serde_json::from_value(listing).expect("mock shipyard listing matches the model")
+}
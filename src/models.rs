@@ -0,0 +1,408 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+pub mod market;
+pub mod ship;
+pub mod survey;
+pub mod symbols;
+pub mod waypoint;
+
+pub use market::*;
+pub use ship::*;
+pub use survey::*;
+pub use symbols::*;
+pub use waypoint::*;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Contract {
+    pub accepted: bool,
+    #[serde(rename = "deadlineToAccept")]
+    pub deadline_to_accept: DateTime<Utc>,
+    #[serde(rename = "expiration")]
+    pub expiration_date: DateTime<Utc>,
+    #[serde(rename = "factionSymbol")]
+    pub faction_symbol: String,
+    pub fulfilled: bool,
+    pub id: String,
+    pub terms: Terms,
+    #[serde(rename = "type")]
+    pub contract_type: ContractType,
+}
+
+impl fmt::Display for Contract {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Contract {{ \
+            accepted: {}, \
+            deadline_to_accept: {}, \
+            expiration_date: {}, \
+            faction_symbol: {}, \
+            fulfilled: {}, \
+            id: {}, \
+            terms: {}, \
+            type: {} }}",
+            self.accepted,
+            self.deadline_to_accept,
+            self.expiration_date,
+            self.faction_symbol,
+            self.fulfilled,
+            self.id,
+            self.terms,
+            self.contract_type
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Terms {
+    pub deadline: DateTime<Utc>,
+    #[serde(rename = "deliver")]
+    pub deliveries: Vec<Delivery>,
+    #[serde(rename = "payment")]
+    pub payment: Payment,
+}
+
+impl fmt::Display for Terms {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Terms {{ \
+            deadline: {}, \
+            deliveries: {:?}, \
+            payment: {} }}",
+            self.deadline, self.deliveries, self.payment
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Payment {
+    #[serde(rename = "onAccepted")]
+    pub payment_on_accepted: u64,
+    #[serde(rename = "onFulfilled")]
+    pub payment_on_fulfilled: u64,
+}
+
+impl fmt::Display for Payment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Payment {{ \
+            payment_on_accepted: {}, \
+            payment_on_fulfilled: {} }}",
+            self.payment_on_accepted, self.payment_on_fulfilled
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Delivery {
+    #[serde(rename = "destinationSymbol")]
+    pub destination_symbol: String,
+    #[serde(rename = "tradeSymbol")]
+    pub trade_symbol: TradeSymbol,
+    #[serde(rename = "unitsFulfilled")]
+    pub units_fulfilled: u64,
+    #[serde(rename = "unitsRequired")]
+    pub units_required: u64,
+}
+
+impl fmt::Display for Delivery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Delivery {{ \
+            destination_symbol: {}, \
+            trade_symbol: {}, \
+            units_fulfilled: {}, \
+            units_required: {} }}",
+            self.destination_symbol, self.trade_symbol, self.units_fulfilled, self.units_required
+        )
+    }
+}
+
+impl Delivery {
+    pub fn units_remaining(&self) -> u64 {
+        self.units_required.saturating_sub(self.units_fulfilled)
+    }
+}
+
+/// Returned by accepting and fulfilling a contract.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ContractResult {
+    pub agent: AgentData,
+    pub contract: Contract,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ContractResultResponse {
+    pub data: ContractResult,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NegotiateContractResult {
+    pub contract: Contract,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NegotiateContractResponse {
+    pub data: NegotiateContractResult,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeliverResult {
+    pub contract: Contract,
+    pub cargo: Cargo,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeliverResponse {
+    pub data: DeliverResult,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AgentData {
+    #[serde(rename = "accountId")]
+    pub account_id: String,
+    pub credits: u64,
+    pub headquarters: String,
+    #[serde(rename = "shipCount")]
+    pub ship_count: u64,
+    #[serde(rename = "startingFaction")]
+    pub starting_faction: String,
+    pub symbol: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Meta {
+    pub limit: u32,
+    pub page: u32,
+    pub total: u32,
+}
+
+/// One page of any list endpoint.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListResponse<T> {
+    pub data: Vec<T>,
+    pub meta: Meta,
+}
+
+pub type ContractResponse = ListResponse<Contract>;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AgentDataResponse {
+    pub data: AgentData,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ViewAvailableShipsResponse {
+    pub data: AvailableShips,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AvailableShips {
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(rename = "shipTypes", default)]
+    pub ship_types: Option<Vec<ShipyardShipType>>,
+    #[serde(default)]
+    pub transactions: Option<Vec<Transaction>>,
+    #[serde(default)]
+    pub ships: Option<Vec<ShipyardShip>>,
+    #[serde(rename = "modificationsFee", default)]
+    pub modifications_fee: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Transaction {
+    #[serde(rename = "shipSymbol", default)]
+    pub ship_symbol: Option<String>,
+    #[serde(rename = "shipType", default)]
+    pub ship_type: Option<ShipType>,
+    #[serde(rename = "waypointSymbol", default)]
+    pub waypoint_symbol: Option<String>,
+    #[serde(default)]
+    pub price: Option<u64>,
+    #[serde(default)]
+    pub timestamp: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PurchaseShipResult {
+    pub agent: AgentData,
+    pub ship: Ship,
+    pub transaction: Transaction,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PurchaseShipResponse {
+    pub data: PurchaseShipResult,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShipyardShip {
+    #[serde(rename = "type", default)]
+    pub ship_type: Option<ShipType>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub supply: Option<SupplyLevel>,
+    #[serde(default)]
+    pub activity: Option<ActivityLevel>,
+    #[serde(rename = "purchasePrice", default)]
+    pub purchase_price: Option<u64>,
+    pub frame: Frame,
+    pub reactor: Reactor,
+    pub engine: Engine,
+    #[serde(default)]
+    pub modules: Option<Vec<Module>>,
+    #[serde(default)]
+    pub mounts: Option<Vec<Mount>>,
+    pub crew: Crew,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Frame {
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(rename = "moduleSlots", default)]
+    pub module_slots: Option<u32>,
+    #[serde(rename = "mountingPoints", default)]
+    pub mounting_points: Option<u32>,
+    #[serde(rename = "fuelCapacity", default)]
+    pub fuel_capacity: Option<u32>,
+    #[serde(default)]
+    pub quality: Option<u32>,
+    pub requirements: Requirements,
+    #[serde(default)]
+    pub condition: Option<f64>,
+    #[serde(default)]
+    pub integrity: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Reactor {
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(rename = "powerOutput", default)]
+    pub power_output: Option<u32>,
+    #[serde(default)]
+    pub quality: Option<u32>,
+    pub requirements: Requirements,
+    #[serde(default)]
+    pub condition: Option<f64>,
+    #[serde(default)]
+    pub integrity: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Engine {
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub speed: Option<u32>,
+    #[serde(default)]
+    pub quality: Option<u32>,
+    pub requirements: Requirements,
+    #[serde(default)]
+    pub condition: Option<f64>,
+    #[serde(default)]
+    pub integrity: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Mount {
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub strength: Option<u32>,
+    #[serde(default)]
+    pub deposits: Option<Vec<String>>,
+    pub requirements: Requirements,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Crew {
+    #[serde(default)]
+    pub required: Option<u32>,
+    #[serde(default)]
+    pub capacity: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Requirements {
+    #[serde(default)]
+    pub power: Option<u32>,
+    #[serde(default)]
+    pub crew: Option<i32>,
+    #[serde(default)]
+    pub slots: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Module {
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub capacity: Option<u32>,
+    pub requirements: Requirements,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShipyardShipType {
+    #[serde(rename = "type", default)]
+    pub ship_type: Option<ShipType>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetCargoResponse {
+    pub data: Cargo,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JettisonResult {
+    pub cargo: Cargo,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JettisonResponse {
+    pub data: JettisonResult,
+}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Cargo {
+    pub capacity: u32,
+    #[serde(default)]
+    pub units: u32,
+    pub inventory: Vec<CargoObject>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CargoObject {
+    pub description: String,
+    pub name: String,
+    pub symbol: TradeSymbol,
+    pub units: u32,
+}
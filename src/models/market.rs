@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    ActivityLevel, AgentData, Cargo, MarketTradeGoodType, MarketTransactionType, SupplyLevel,
+    TradeSymbol,
+};
+
+/// A waypoint's marketplace. Prices and recent transactions are only
+/// included while one of our ships is at the waypoint.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Market {
+    pub symbol: String,
+    #[serde(default)]
+    pub exports: Vec<TradeGood>,
+    #[serde(default)]
+    pub imports: Vec<TradeGood>,
+    #[serde(default)]
+    pub exchange: Vec<TradeGood>,
+    #[serde(default)]
+    pub transactions: Vec<MarketTransaction>,
+    #[serde(rename = "tradeGoods", default)]
+    pub trade_goods: Vec<MarketTradeGood>,
+}
+
+impl Market {
+    pub fn trade_good(&self, symbol: &TradeSymbol) -> Option<&MarketTradeGood> {
+        self.trade_goods.iter().find(|good| &good.symbol == symbol)
+    }
+
+    /// Whether the market trades `symbol` at all, priced or not.
+    pub fn trades(&self, symbol: &TradeSymbol) -> bool {
+        self.exports
+            .iter()
+            .chain(&self.imports)
+            .chain(&self.exchange)
+            .any(|good| &good.symbol == symbol)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TradeGood {
+    pub symbol: TradeSymbol,
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MarketTradeGood {
+    pub symbol: TradeSymbol,
+    #[serde(rename = "type")]
+    pub trade_type: MarketTradeGoodType,
+    #[serde(rename = "tradeVolume")]
+    pub trade_volume: u32,
+    pub supply: SupplyLevel,
+    #[serde(default)]
+    pub activity: Option<ActivityLevel>,
+    #[serde(rename = "purchasePrice")]
+    pub purchase_price: u64,
+    #[serde(rename = "sellPrice")]
+    pub sell_price: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MarketTransaction {
+    #[serde(rename = "waypointSymbol")]
+    pub waypoint_symbol: String,
+    #[serde(rename = "shipSymbol")]
+    pub ship_symbol: String,
+    #[serde(rename = "tradeSymbol")]
+    pub trade_symbol: TradeSymbol,
+    #[serde(rename = "type")]
+    pub transaction_type: MarketTransactionType,
+    pub units: u32,
+    #[serde(rename = "pricePerUnit")]
+    pub price_per_unit: u64,
+    #[serde(rename = "totalPrice")]
+    pub total_price: u64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Result of selling or purchasing cargo.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TradeResult {
+    pub agent: AgentData,
+    pub cargo: Cargo,
+    pub transaction: MarketTransaction,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetMarketResponse {
+    pub data: Market,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TradeResponse {
+    pub data: TradeResult,
+}
@@ -0,0 +1,178 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    AgentData, Cargo, Engine, Frame, ListResponse, MarketTransaction, Module, Mount, Reactor,
+    ShipNavFlightMode, ShipNavStatus, ShipRole, TradeSymbol, WaypointType,
+};
+
+/// A ship owned by the agent, as returned by `GET /my/ships`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Ship {
+    pub symbol: String,
+    pub registration: ShipRegistration,
+    pub nav: ShipNav,
+    pub crew: ShipCrew,
+    pub frame: Frame,
+    pub reactor: Reactor,
+    pub engine: Engine,
+    pub cooldown: Cooldown,
+    #[serde(default)]
+    pub modules: Vec<Module>,
+    #[serde(default)]
+    pub mounts: Vec<Mount>,
+    pub cargo: Cargo,
+    pub fuel: ShipFuel,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShipRegistration {
+    pub name: String,
+    #[serde(rename = "factionSymbol")]
+    pub faction_symbol: String,
+    pub role: ShipRole,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShipNav {
+    #[serde(rename = "systemSymbol")]
+    pub system_symbol: String,
+    #[serde(rename = "waypointSymbol")]
+    pub waypoint_symbol: String,
+    pub route: ShipNavRoute,
+    pub status: ShipNavStatus,
+    #[serde(rename = "flightMode")]
+    pub flight_mode: ShipNavFlightMode,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShipNavRoute {
+    pub destination: ShipNavRouteWaypoint,
+    pub origin: ShipNavRouteWaypoint,
+    #[serde(rename = "departureTime")]
+    pub departure_time: DateTime<Utc>,
+    pub arrival: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShipNavRouteWaypoint {
+    pub symbol: String,
+    #[serde(rename = "type")]
+    pub waypoint_type: WaypointType,
+    #[serde(rename = "systemSymbol")]
+    pub system_symbol: String,
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShipCrew {
+    pub current: i32,
+    pub required: i32,
+    pub capacity: i32,
+    pub rotation: String,
+    pub morale: u32,
+    pub wages: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Cooldown {
+    #[serde(rename = "shipSymbol")]
+    pub ship_symbol: String,
+    #[serde(rename = "totalSeconds")]
+    pub total_seconds: u64,
+    #[serde(rename = "remainingSeconds")]
+    pub remaining_seconds: u64,
+    #[serde(default)]
+    pub expiration: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShipFuel {
+    pub current: u32,
+    pub capacity: u32,
+    #[serde(default)]
+    pub consumed: Option<FuelConsumed>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FuelConsumed {
+    pub amount: u32,
+    pub timestamp: DateTime<Utc>,
+}
+
+pub type ShipsResponse = ListResponse<Ship>;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetShipResponse {
+    pub data: Ship,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NavResult {
+    pub nav: ShipNav,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NavResponse {
+    pub data: NavResult,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NavigateResult {
+    pub fuel: ShipFuel,
+    pub nav: ShipNav,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NavigateResponse {
+    pub data: NavigateResult,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RefuelResult {
+    pub agent: AgentData,
+    pub fuel: ShipFuel,
+    pub transaction: MarketTransaction,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RefuelResponse {
+    pub data: RefuelResult,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetShipNavResponse {
+    pub data: ShipNav,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetCooldownResponse {
+    pub data: Cooldown,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExtractionYield {
+    pub symbol: TradeSymbol,
+    pub units: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Extraction {
+    #[serde(rename = "shipSymbol")]
+    pub ship_symbol: String,
+    #[serde(rename = "yield")]
+    pub extraction_yield: ExtractionYield,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExtractResult {
+    pub extraction: Extraction,
+    pub cooldown: Cooldown,
+    pub cargo: Cargo,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExtractResponse {
+    pub data: ExtractResult,
+}
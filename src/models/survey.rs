@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{Cooldown, SurveySize, TradeSymbol};
+
+/// A survey of a waypoint's deposits. Sent back verbatim with an extraction
+/// to bias the yield towards its deposits.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Survey {
+    pub signature: String,
+    /// The waypoint that was surveyed.
+    pub symbol: String,
+    pub deposits: Vec<SurveyDeposit>,
+    pub expiration: DateTime<Utc>,
+    pub size: SurveySize,
+}
+
+impl Survey {
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expiration <= now
+    }
+
+    /// Share of the deposits that are one of `desired`. A deposit listed
+    /// twice is twice as likely to be extracted, so duplicates count.
+    pub fn desired_ratio(&self, desired: &[TradeSymbol]) -> f64 {
+        if self.deposits.is_empty() {
+            return 0.0;
+        }
+        let matching = self
+            .deposits
+            .iter()
+            .filter(|deposit| desired.contains(&deposit.symbol))
+            .count();
+        matching as f64 / self.deposits.len() as f64
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SurveyDeposit {
+    pub symbol: TradeSymbol,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SurveyResult {
+    pub cooldown: Cooldown,
+    pub surveys: Vec<Survey>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SurveyResponse {
+    pub data: SurveyResult,
+}
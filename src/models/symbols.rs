@@ -0,0 +1,360 @@
+use std::{convert::Infallible, fmt, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Declares an enum over one of the API's string vocabularies. Values the
+/// server adds later deserialize into `Unknown` instead of failing.
+macro_rules! string_enum {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident => $value:literal,)* }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum $name {
+            $($variant,)*
+            Unknown(String),
+        }
+
+        impl $name {
+            pub const ALL: &'static [$name] = &[$($name::$variant,)*];
+
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(value) => value,
+                }
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Infallible;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                Ok(match value {
+                    $($value => $name::$variant,)*
+                    other => $name::Unknown(other.to_string()),
+                })
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                let Ok(parsed) = value.parse();
+                parsed
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = String::deserialize(deserializer)?;
+                Ok($name::from(value.as_str()))
+            }
+        }
+    };
+}
+
+string_enum! {
+    /// Hull models sold at shipyards.
+    ShipType {
+        ShipProbe => "SHIP_PROBE",
+        ShipMiningDrone => "SHIP_MINING_DRONE",
+        ShipSiphonDrone => "SHIP_SIPHON_DRONE",
+        ShipInterceptor => "SHIP_INTERCEPTOR",
+        ShipLightHauler => "SHIP_LIGHT_HAULER",
+        ShipCommandFrigate => "SHIP_COMMAND_FRIGATE",
+        ShipExplorer => "SHIP_EXPLORER",
+        ShipHeavyFreighter => "SHIP_HEAVY_FREIGHTER",
+        ShipLightShuttle => "SHIP_LIGHT_SHUTTLE",
+        ShipOreHound => "SHIP_ORE_HOUND",
+        ShipRefiningFreighter => "SHIP_REFINING_FREIGHTER",
+        ShipSurveyor => "SHIP_SURVEYOR",
+        ShipBulkFreighter => "SHIP_BULK_FREIGHTER",
+    }
+}
+
+string_enum! {
+    /// The role a ship was registered with.
+    ShipRole {
+        Fabricator => "FABRICATOR",
+        Harvester => "HARVESTER",
+        Hauler => "HAULER",
+        Interceptor => "INTERCEPTOR",
+        Excavator => "EXCAVATOR",
+        Transport => "TRANSPORT",
+        Repair => "REPAIR",
+        Surveyor => "SURVEYOR",
+        Command => "COMMAND",
+        Carrier => "CARRIER",
+        Patrol => "PATROL",
+        Satellite => "SATELLITE",
+        Explorer => "EXPLORER",
+        Refinery => "REFINERY",
+    }
+}
+
+string_enum! {
+    ShipNavStatus {
+        InTransit => "IN_TRANSIT",
+        InOrbit => "IN_ORBIT",
+        Docked => "DOCKED",
+    }
+}
+
+string_enum! {
+    ShipNavFlightMode {
+        Drift => "DRIFT",
+        Stealth => "STEALTH",
+        Cruise => "CRUISE",
+        Burn => "BURN",
+    }
+}
+
+string_enum! {
+    WaypointType {
+        Planet => "PLANET",
+        GasGiant => "GAS_GIANT",
+        Moon => "MOON",
+        OrbitalStation => "ORBITAL_STATION",
+        JumpGate => "JUMP_GATE",
+        AsteroidField => "ASTEROID_FIELD",
+        Asteroid => "ASTEROID",
+        EngineeredAsteroid => "ENGINEERED_ASTEROID",
+        AsteroidBase => "ASTEROID_BASE",
+        Nebula => "NEBULA",
+        DebrisField => "DEBRIS_FIELD",
+        GravityWell => "GRAVITY_WELL",
+        ArtificialGravityWell => "ARTIFICIAL_GRAVITY_WELL",
+        FuelStation => "FUEL_STATION",
+    }
+}
+
+string_enum! {
+    WaypointTraitSymbol {
+        Uncharted => "UNCHARTED",
+        UnderConstruction => "UNDER_CONSTRUCTION",
+        Marketplace => "MARKETPLACE",
+        Shipyard => "SHIPYARD",
+        Outpost => "OUTPOST",
+        ScatteredSettlements => "SCATTERED_SETTLEMENTS",
+        SprawlingCities => "SPRAWLING_CITIES",
+        MegaStructures => "MEGA_STRUCTURES",
+        PirateBase => "PIRATE_BASE",
+        Overcrowded => "OVERCROWDED",
+        HighTech => "HIGH_TECH",
+        Corrupt => "CORRUPT",
+        Bureaucratic => "BUREAUCRATIC",
+        TradingHub => "TRADING_HUB",
+        Industrial => "INDUSTRIAL",
+        BlackMarket => "BLACK_MARKET",
+        ResearchFacility => "RESEARCH_FACILITY",
+        MilitaryBase => "MILITARY_BASE",
+        SurveillanceOutpost => "SURVEILLANCE_OUTPOST",
+        ExplorationOutpost => "EXPLORATION_OUTPOST",
+        MineralDeposits => "MINERAL_DEPOSITS",
+        CommonMetalDeposits => "COMMON_METAL_DEPOSITS",
+        PreciousMetalDeposits => "PRECIOUS_METAL_DEPOSITS",
+        RareMetalDeposits => "RARE_METAL_DEPOSITS",
+        MethanePools => "METHANE_POOLS",
+        IceCrystals => "ICE_CRYSTALS",
+        ExplosiveGases => "EXPLOSIVE_GASES",
+        StrongMagnetosphere => "STRONG_MAGNETOSPHERE",
+        VibrantAuroras => "VIBRANT_AURORAS",
+        SaltFlats => "SALT_FLATS",
+        Canyons => "CANYONS",
+        PerpetualDaylight => "PERPETUAL_DAYLIGHT",
+        PerpetualOvercast => "PERPETUAL_OVERCAST",
+        DrySeabeds => "DRY_SEABEDS",
+        MagmaSeas => "MAGMA_SEAS",
+        Supervolcanoes => "SUPERVOLCANOES",
+        AshClouds => "ASH_CLOUDS",
+        VastRuins => "VAST_RUINS",
+        MutatedFlora => "MUTATED_FLORA",
+        Terraformed => "TERRAFORMED",
+        ExtremeTemperatures => "EXTREME_TEMPERATURES",
+        ExtremePressure => "EXTREME_PRESSURE",
+        DiverseLife => "DIVERSE_LIFE",
+        ScarceLife => "SCARCE_LIFE",
+        Fossils => "FOSSILS",
+        WeakGravity => "WEAK_GRAVITY",
+        StrongGravity => "STRONG_GRAVITY",
+        CrushingGravity => "CRUSHING_GRAVITY",
+        ToxicAtmosphere => "TOXIC_ATMOSPHERE",
+        CorrosiveAtmosphere => "CORROSIVE_ATMOSPHERE",
+        BreathableAtmosphere => "BREATHABLE_ATMOSPHERE",
+        ThinAtmosphere => "THIN_ATMOSPHERE",
+        Jovian => "JOVIAN",
+        Rocky => "ROCKY",
+        Volcanic => "VOLCANIC",
+        Frozen => "FROZEN",
+        Swamp => "SWAMP",
+        Barren => "BARREN",
+        Temperate => "TEMPERATE",
+        Jungle => "JUNGLE",
+        Ocean => "OCEAN",
+        Radioactive => "RADIOACTIVE",
+        MicroGravityAnomalies => "MICRO_GRAVITY_ANOMALIES",
+        DebrisCluster => "DEBRIS_CLUSTER",
+        DeepCraters => "DEEP_CRATERS",
+        ShallowCraters => "SHALLOW_CRATERS",
+        UnstableComposition => "UNSTABLE_COMPOSITION",
+        HollowedInterior => "HOLLOWED_INTERIOR",
+        Stripped => "STRIPPED",
+    }
+}
+
+impl WaypointTraitSymbol {
+    /// Goods that can be extracted at an asteroid with this trait; empty for
+    /// traits that aren't deposits.
+    pub fn deposits(&self) -> Vec<TradeSymbol> {
+        use TradeSymbol::*;
+        match self {
+            WaypointTraitSymbol::CommonMetalDeposits => vec![IronOre, CopperOre, AluminumOre],
+            WaypointTraitSymbol::PreciousMetalDeposits => vec![SilverOre, GoldOre, PlatinumOre],
+            WaypointTraitSymbol::MineralDeposits => {
+                vec![QuartzSand, SiliconCrystals, PreciousStones]
+            }
+            WaypointTraitSymbol::RareMetalDeposits => vec![UraniteOre],
+            WaypointTraitSymbol::IceCrystals => vec![IceWater, AmmoniaIce],
+            _ => Vec::new(),
+        }
+    }
+}
+
+string_enum! {
+    /// Goods that can be mined, traded or delivered.
+    TradeSymbol {
+        PreciousStones => "PRECIOUS_STONES",
+        QuartzSand => "QUARTZ_SAND",
+        SiliconCrystals => "SILICON_CRYSTALS",
+        AmmoniaIce => "AMMONIA_ICE",
+        LiquidHydrogen => "LIQUID_HYDROGEN",
+        LiquidNitrogen => "LIQUID_NITROGEN",
+        IceWater => "ICE_WATER",
+        ExoticMatter => "EXOTIC_MATTER",
+        AdvancedCircuitry => "ADVANCED_CIRCUITRY",
+        GravitonEmitters => "GRAVITON_EMITTERS",
+        Iron => "IRON",
+        IronOre => "IRON_ORE",
+        Copper => "COPPER",
+        CopperOre => "COPPER_ORE",
+        Aluminum => "ALUMINUM",
+        AluminumOre => "ALUMINUM_ORE",
+        Silver => "SILVER",
+        SilverOre => "SILVER_ORE",
+        Gold => "GOLD",
+        GoldOre => "GOLD_ORE",
+        Platinum => "PLATINUM",
+        PlatinumOre => "PLATINUM_ORE",
+        Diamonds => "DIAMONDS",
+        Uranite => "URANITE",
+        UraniteOre => "URANITE_ORE",
+        Meritium => "MERITIUM",
+        MeritiumOre => "MERITIUM_ORE",
+        Hydrocarbon => "HYDROCARBON",
+        Antimatter => "ANTIMATTER",
+        FabMats => "FAB_MATS",
+        Fertilizers => "FERTILIZERS",
+        Fabrics => "FABRICS",
+        Food => "FOOD",
+        Jewelry => "JEWELRY",
+        Machinery => "MACHINERY",
+        Firearms => "FIREARMS",
+        AssaultRifles => "ASSAULT_RIFLES",
+        MilitaryEquipment => "MILITARY_EQUIPMENT",
+        Explosives => "EXPLOSIVES",
+        LabInstruments => "LAB_INSTRUMENTS",
+        Ammunition => "AMMUNITION",
+        Electronics => "ELECTRONICS",
+        ShipPlating => "SHIP_PLATING",
+        ShipParts => "SHIP_PARTS",
+        Equipment => "EQUIPMENT",
+        Fuel => "FUEL",
+        Medicine => "MEDICINE",
+        Drugs => "DRUGS",
+        Clothing => "CLOTHING",
+        Microprocessors => "MICROPROCESSORS",
+        Plastics => "PLASTICS",
+        Polynucleotides => "POLYNUCLEOTIDES",
+        Biocomposites => "BIOCOMPOSITES",
+        QuantumStabilizers => "QUANTUM_STABILIZERS",
+        Nanobots => "NANOBOTS",
+        AiMainframes => "AI_MAINFRAMES",
+        QuantumDrives => "QUANTUM_DRIVES",
+        RoboticDrones => "ROBOTIC_DRONES",
+        CyberImplants => "CYBER_IMPLANTS",
+        GeneTherapeutics => "GENE_THERAPEUTICS",
+        NeuralChips => "NEURAL_CHIPS",
+        MoodRegulators => "MOOD_REGULATORS",
+        ViralAgents => "VIRAL_AGENTS",
+        MicroFusionGenerators => "MICRO_FUSION_GENERATORS",
+        Supergrains => "SUPERGRAINS",
+        LaserRifles => "LASER_RIFLES",
+        Holographics => "HOLOGRAPHICS",
+        ShipSalvage => "SHIP_SALVAGE",
+        RelicTech => "RELIC_TECH",
+        NovelLifeforms => "NOVEL_LIFEFORMS",
+        BotanicalSpecimens => "BOTANICAL_SPECIMENS",
+        CulturalArtifacts => "CULTURAL_ARTIFACTS",
+    }
+}
+
+string_enum! {
+    ContractType {
+        Procurement => "PROCUREMENT",
+        Transport => "TRANSPORT",
+        Shuttle => "SHUTTLE",
+    }
+}
+
+string_enum! {
+    /// How many extractions a survey is good for before it is exhausted.
+    SurveySize {
+        Small => "SMALL",
+        Moderate => "MODERATE",
+        Large => "LARGE",
+    }
+}
+
+string_enum! {
+    SupplyLevel {
+        Scarce => "SCARCE",
+        Limited => "LIMITED",
+        Moderate => "MODERATE",
+        High => "HIGH",
+        Abundant => "ABUNDANT",
+    }
+}
+
+string_enum! {
+    ActivityLevel {
+        Weak => "WEAK",
+        Growing => "GROWING",
+        Strong => "STRONG",
+        Restricted => "RESTRICTED",
+    }
+}
+
+string_enum! {
+    MarketTradeGoodType {
+        Export => "EXPORT",
+        Import => "IMPORT",
+        Exchange => "EXCHANGE",
+    }
+}
+
+string_enum! {
+    MarketTransactionType {
+        Purchase => "PURCHASE",
+        Sell => "SELL",
+    }
+}
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+
+use super::{ListResponse, TradeSymbol, WaypointTraitSymbol, WaypointType};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Waypoint {
+    pub symbol: String,
+    #[serde(rename = "systemSymbol")]
+    pub system_symbol: String,
+    #[serde(rename = "type")]
+    pub waypoint_type: WaypointType,
+    pub x: i32,
+    pub y: i32,
+    #[serde(default)]
+    pub orbitals: Vec<WaypointOrbital>,
+    #[serde(default)]
+    pub orbits: Option<String>,
+    #[serde(default)]
+    pub faction: Option<Faction>,
+    #[serde(default)]
+    pub traits: Vec<Trait>,
+    #[serde(default)]
+    pub modifiers: Vec<WaypointModifier>,
+    #[serde(default)]
+    pub chart: Option<Chart>,
+    #[serde(rename = "isUnderConstruction", default)]
+    pub is_under_construction: bool,
+}
+
+impl Waypoint {
+    pub fn has_trait(&self, symbol: &WaypointTraitSymbol) -> bool {
+        self.traits.iter().any(|t| &t.symbol == symbol)
+    }
+
+    /// Whether one of the waypoint's deposit traits yields `symbol`.
+    pub fn has_deposit(&self, symbol: &TradeSymbol) -> bool {
+        self.traits
+            .iter()
+            .any(|t| t.symbol.deposits().contains(symbol))
+    }
+
+    pub fn distance_to(&self, other: &Waypoint) -> f64 {
+        let dx = (self.x - other.x) as f64;
+        let dy = (self.y - other.y) as f64;
+        (dx * dx + dy * dy).sqrt()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WaypointOrbital {
+    pub symbol: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WaypointModifier {
+    pub symbol: String,
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Chart {
+    #[serde(rename = "waypointSymbol", default)]
+    pub waypoint_symbol: Option<String>,
+    #[serde(rename = "submittedBy", default)]
+    pub submitted_by: Option<String>,
+    #[serde(rename = "submittedOn", default)]
+    pub submitted_on: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Faction {
+    pub symbol: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Trait {
+    pub description: String,
+    pub name: String,
+    pub symbol: WaypointTraitSymbol,
+}
+
+/// A star system and the waypoints it contains, from `GET /systems/{symbol}`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct System {
+    pub symbol: String,
+    #[serde(rename = "sectorSymbol")]
+    pub sector_symbol: String,
+    #[serde(rename = "type")]
+    pub system_type: String,
+    pub x: i32,
+    pub y: i32,
+    #[serde(default)]
+    pub waypoints: Vec<SystemWaypoint>,
+    #[serde(default)]
+    pub factions: Vec<Faction>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SystemWaypoint {
+    pub symbol: String,
+    #[serde(rename = "type")]
+    pub waypoint_type: WaypointType,
+    pub x: i32,
+    pub y: i32,
+    #[serde(default)]
+    pub orbitals: Vec<WaypointOrbital>,
+    #[serde(default)]
+    pub orbits: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetSystemResponse {
+    pub data: System,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetWaypointResponse {
+    pub data: Waypoint,
+}
+
+pub type FindShipyardResponse = ListResponse<Waypoint>;
@@ -0,0 +1,182 @@
+use std::{collections::HashSet, sync::Arc};
+
+use crate::client::ApiClient;
+use crate::error::{Result, SpaceTradersError};
+use crate::models::{Ship, ShipNavFlightMode, ShipNavStatus, TradeSymbol, WaypointTraitSymbol};
+use crate::routing::{RouteObjective, RoutePlanner, ShipSpecs};
+use crate::scheduler::Scheduler;
+use crate::waypoints::WaypointQuery;
+
+impl ApiClient {
+    /// Builds a route planner for `system`, checking every marketplace to see
+    /// which ones sell fuel.
+    pub async fn route_planner(&self, system: &str) -> Result<RoutePlanner> {
+        let waypoints = self.find_waypoints(&WaypointQuery::new(system)).await?;
+        let mut fuel_stations = HashSet::new();
+        for waypoint in waypoints
+            .iter()
+            .filter(|waypoint| waypoint.has_trait(&WaypointTraitSymbol::Marketplace))
+        {
+            let market = self.get_market(system, &waypoint.symbol).await?;
+            if market.trades(&TradeSymbol::Fuel) {
+                fuel_stations.insert(waypoint.symbol.clone());
+            }
+        }
+        Ok(RoutePlanner::from_waypoints(&waypoints, |waypoint| {
+            fuel_stations.contains(&waypoint.symbol)
+        }))
+    }
+}
+
+/// Drives ships between waypoints, handling the orbit/dock/refuel
+/// transitions each leg needs. Every method keeps the passed `Ship` in sync
+/// with what the API reported, so callers can keep using it afterwards.
+#[derive(Debug, Clone)]
+pub struct Navigator {
+    client: ApiClient,
+    planner: Arc<RoutePlanner>,
+    scheduler: Arc<Scheduler>,
+    objective: RouteObjective,
+}
+
+impl Navigator {
+    pub fn new(client: ApiClient, planner: Arc<RoutePlanner>) -> Self {
+        let scheduler = Arc::new(Scheduler::new(client.clock().clone()));
+        Navigator {
+            client,
+            planner,
+            scheduler,
+            objective: RouteObjective::Fastest,
+        }
+    }
+
+    /// Shares `scheduler` with other ship tasks instead of keeping a private one.
+    pub fn with_scheduler(mut self, scheduler: Arc<Scheduler>) -> Self {
+        self.scheduler = scheduler;
+        self
+    }
+
+    pub fn with_objective(mut self, objective: RouteObjective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    pub fn objective(&self) -> RouteObjective {
+        self.objective
+    }
+
+    pub fn client(&self) -> &ApiClient {
+        &self.client
+    }
+
+    pub fn planner(&self) -> &RoutePlanner {
+        &self.planner
+    }
+
+    pub fn scheduler(&self) -> &Arc<Scheduler> {
+        &self.scheduler
+    }
+
+    /// Sleeps until the ship's current flight lands, if it is in transit.
+    pub async fn wait_for_arrival(&self, ship: &mut Ship) -> Result<()> {
+        if ship.nav.status != ShipNavStatus::InTransit {
+            return Ok(());
+        }
+        println!(
+            "{} in transit to {}, arriving at {}",
+            ship.symbol, ship.nav.route.destination.symbol, ship.nav.route.arrival
+        );
+        self.scheduler.record_nav(&ship.symbol, &ship.nav);
+        self.scheduler.wait_for_arrival(&ship.symbol).await;
+        ship.nav.status = ShipNavStatus::InOrbit;
+        ship.nav.waypoint_symbol = ship.nav.route.destination.symbol.clone();
+        Ok(())
+    }
+
+    pub async fn ensure_in_orbit(&self, ship: &mut Ship) -> Result<()> {
+        self.wait_for_arrival(ship).await?;
+        if ship.nav.status == ShipNavStatus::Docked {
+            ship.nav = self.client.send_ship_to_orbit(&ship.symbol).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn ensure_docked(&self, ship: &mut Ship) -> Result<()> {
+        self.wait_for_arrival(ship).await?;
+        if ship.nav.status != ShipNavStatus::Docked {
+            ship.nav = self.client.dock_ship(&ship.symbol).await?;
+        }
+        Ok(())
+    }
+
+    /// Docks and fills the tank at the ship's current waypoint.
+    pub async fn refuel(&self, ship: &mut Ship) -> Result<()> {
+        if ship.fuel.capacity == 0 || ship.fuel.current >= ship.fuel.capacity {
+            return Ok(());
+        }
+        self.ensure_docked(ship).await?;
+        let refuel = self.client.refuel_ship(&ship.symbol).await?;
+        println!(
+            "{} refuelled {} units for {}",
+            ship.symbol, refuel.transaction.units, refuel.transaction.total_price
+        );
+        ship.fuel = refuel.fuel;
+        Ok(())
+    }
+
+    /// Flies a single leg and waits for it to land.
+    pub async fn fly(
+        &self,
+        ship: &mut Ship,
+        destination: &str,
+        flight_mode: &ShipNavFlightMode,
+    ) -> Result<()> {
+        self.ensure_in_orbit(ship).await?;
+        if &ship.nav.flight_mode != flight_mode {
+            ship.nav = self
+                .client
+                .set_flight_mode(&ship.symbol, flight_mode)
+                .await?;
+        }
+        let result = self
+            .client
+            .navigate_to_waypoint(&ship.symbol, destination)
+            .await?;
+        ship.nav = result.nav;
+        ship.fuel = result.fuel;
+        self.wait_for_arrival(ship).await
+    }
+
+    /// Takes the ship to `destination` along the planned route, refuelling
+    /// at intermediate stops. The ship ends up in orbit at the destination.
+    pub async fn go_to(&self, ship: &mut Ship, destination: &str) -> Result<()> {
+        self.wait_for_arrival(ship).await?;
+        if ship.nav.waypoint_symbol == destination {
+            return Ok(());
+        }
+        let route = self
+            .planner
+            .plan(
+                &ship.nav.waypoint_symbol,
+                destination,
+                &ShipSpecs::from_ship(ship),
+                self.objective,
+            )
+            .ok_or_else(|| SpaceTradersError::NoRoute {
+                from: ship.nav.waypoint_symbol.clone(),
+                to: destination.to_string(),
+            })?;
+
+        for leg in &route.legs {
+            if leg.refuel_before {
+                self.refuel(ship).await?;
+            }
+            println!(
+                "{} flying {} -> {} ({}, {} fuel, {}s)",
+                ship.symbol, leg.from, leg.to, leg.flight_mode, leg.fuel_cost, leg.travel_seconds
+            );
+            self.fly(ship, &leg.to, &leg.flight_mode).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use futures::{stream, Stream, TryStreamExt};
+use serde::de::DeserializeOwned;
+
+use crate::client::ApiClient;
+use crate::error::Result;
+use crate::models::{Contract, ListResponse, Ship};
+
+/// Largest page size the API accepts.
+pub const PAGE_LIMIT: u32 = 20;
+
+fn page_path(path: &str, page: u32, limit: u32) -> String {
+    let separator = if path.contains('?') { '&' } else { '?' };
+    format!("{}{}page={}&limit={}", path, separator, page, limit)
+}
+
+impl ApiClient {
+    /// Streams every item of a list endpoint, fetching `PAGE_LIMIT` items at a
+    /// time until `meta.total` is reached. Each page goes through the normal
+    /// request path, so the rate limiter and retry policy apply.
+    pub fn paginate<'a, T>(&'a self, path: impl Into<String>) -> impl Stream<Item = Result<T>> + 'a
+    where
+        T: DeserializeOwned + 'a,
+    {
+        let path = path.into();
+        stream::try_unfold(Some(1u32), move |page| {
+            let path = path.clone();
+            async move {
+                let Some(page) = page else {
+                    return Result::Ok(None);
+                };
+                let body: ListResponse<T> = self.get(&page_path(&path, page, PAGE_LIMIT)).await?;
+                let seen = page.saturating_mul(body.meta.limit.max(1));
+                let next = if body.data.is_empty() || seen >= body.meta.total {
+                    None
+                } else {
+                    Some(page + 1)
+                };
+                Ok(Some((
+                    stream::iter(body.data.into_iter().map(Result::Ok)),
+                    next,
+                )))
+            }
+        })
+        .try_flatten()
+    }
+
+    /// Every page of a list endpoint, collected.
+    pub async fn fetch_all<T: DeserializeOwned>(&self, path: impl Into<String>) -> Result<Vec<T>> {
+        self.paginate(path).try_collect().await
+    }
+
+    pub fn ships(&self) -> impl Stream<Item = Result<Ship>> + '_ {
+        self.paginate("/my/ships")
+    }
+
+    pub fn contracts(&self) -> impl Stream<Item = Result<Contract>> + '_ {
+        self.paginate("/my/contracts")
+    }
+}
@@ -0,0 +1,341 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use reqwest::{header::HeaderMap, StatusCode};
+use tokio::sync::Mutex;
+
+use crate::clock::{Clock, SystemClock};
+
+pub const RETRY_AFTER: &str = "retry-after";
+pub const RATE_LIMIT_REMAINING: &str = "x-ratelimit-remaining";
+pub const RATE_LIMIT_RESET: &str = "x-ratelimit-reset";
+
+/// Request budget enforced by the server: a steady per-second rate plus a
+/// burst pool that can be spent on top of it and refills over a window.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitConfig {
+    pub per_second: f64,
+    pub burst: u32,
+    pub burst_window: Duration,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            per_second: 2.0,
+            burst: 30,
+            burst_window: Duration::from_secs(60),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Buckets {
+    steady: f64,
+    burst: f64,
+    last_refill: Instant,
+    blocked_until: Option<Instant>,
+}
+
+/// Token-bucket limiter shared by every request an `ApiClient` sends.
+///
+/// Callers wait on a FIFO lock, so ships queue in the order they asked for a
+/// slot and a busy ship can't starve the others.
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    clock: Arc<dyn Clock>,
+    buckets: Mutex<Buckets>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(RateLimitConfig::default(), Arc::new(SystemClock))
+    }
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig, clock: Arc<dyn Clock>) -> Self {
+        let now = clock.now();
+        RateLimiter {
+            config,
+            clock,
+            buckets: Mutex::new(Buckets {
+                steady: config.per_second.max(1.0),
+                burst: config.burst as f64,
+                last_refill: now,
+                blocked_until: None,
+            }),
+        }
+    }
+
+    pub fn config(&self) -> RateLimitConfig {
+        self.config
+    }
+
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    fn refill(&self, buckets: &mut Buckets, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(buckets.last_refill)
+            .as_secs_f64();
+        let burst_rate = self.config.burst as f64 / self.config.burst_window.as_secs_f64();
+        buckets.steady = (buckets.steady + elapsed * self.config.per_second)
+            .min(self.config.per_second.max(1.0));
+        buckets.burst = (buckets.burst + elapsed * burst_rate).min(self.config.burst as f64);
+        buckets.last_refill = now;
+    }
+
+    /// Waits until a request may be sent and consumes a token for it.
+    pub async fn acquire(&self) {
+        let mut buckets = self.buckets.lock().await;
+        loop {
+            let now = self.clock.now();
+            if let Some(until) = buckets.blocked_until {
+                if until > now {
+                    self.clock.sleep_until(until).await;
+                    continue;
+                }
+                buckets.blocked_until = None;
+            }
+
+            self.refill(&mut buckets, now);
+            if buckets.steady >= 1.0 {
+                buckets.steady -= 1.0;
+                return;
+            }
+            if buckets.burst >= 1.0 {
+                buckets.burst -= 1.0;
+                return;
+            }
+
+            let steady_wait = (1.0 - buckets.steady) / self.config.per_second;
+            let burst_wait = (1.0 - buckets.burst) * self.config.burst_window.as_secs_f64()
+                / self.config.burst as f64;
+            let wait = steady_wait.min(burst_wait).max(0.0);
+            self.clock.sleep(Duration::from_secs_f64(wait)).await;
+        }
+    }
+
+    /// Stops handing out tokens until `duration` has passed.
+    pub async fn block_for(&self, duration: Duration) {
+        let mut buckets = self.buckets.lock().await;
+        let until = self.clock.now() + duration;
+        buckets.steady = 0.0;
+        buckets.burst = 0.0;
+        buckets.blocked_until = Some(buckets.blocked_until.map_or(until, |b| b.max(until)));
+    }
+
+    /// Syncs the local buckets with what the server reported on a response,
+    /// and backs off when the server says we exceeded the limit.
+    pub async fn observe(&self, status: StatusCode, headers: &HeaderMap) {
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let wait = retry_after(headers, self.clock.utc_now()).unwrap_or(Duration::from_secs(1));
+            self.block_for(wait).await;
+            return;
+        }
+        if let Some(remaining) =
+            header_str(headers, RATE_LIMIT_REMAINING).and_then(|value| value.parse::<f64>().ok())
+        {
+            let mut buckets = self.buckets.lock().await;
+            buckets.burst = buckets.burst.min(remaining);
+        }
+    }
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// How long the server asked us to wait, from `Retry-After` (seconds) or
+/// failing that the `x-ratelimit-reset` timestamp, counted from `now`.
+pub fn retry_after(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    if let Some(seconds) =
+        header_str(headers, RETRY_AFTER).and_then(|value| value.parse::<f64>().ok())
+    {
+        return Some(Duration::from_secs_f64(seconds.max(0.0)));
+    }
+    let reset = header_str(headers, RATE_LIMIT_RESET)?;
+    let reset = DateTime::parse_from_rfc3339(reset).ok()?;
+    (reset.with_timezone(&Utc) - now).to_std().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex as StdMutex;
+
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+    use crate::clock::{FakeClock, TokioClock};
+
+    fn limiter(per_second: f64, burst: u32) -> (RateLimiter, Arc<FakeClock>) {
+        let clock = Arc::new(FakeClock::new());
+        let config = RateLimitConfig {
+            per_second,
+            burst,
+            burst_window: Duration::from_secs(60),
+        };
+        (RateLimiter::new(config, clock.clone()), clock)
+    }
+
+    fn assert_secs(actual: Duration, expected: f64) {
+        assert!(
+            (actual.as_secs_f64() - expected).abs() < 1e-6,
+            "expected {}s, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[tokio::test]
+    async fn steady_rate_spaces_requests() {
+        let (limiter, clock) = limiter(2.0, 0);
+        for _ in 0..2 {
+            limiter.acquire().await;
+        }
+        assert_secs(clock.elapsed(), 0.0);
+
+        for _ in 0..4 {
+            limiter.acquire().await;
+        }
+        assert_secs(clock.elapsed(), 2.0);
+    }
+
+    #[tokio::test]
+    async fn burst_is_spent_before_waiting() {
+        let (limiter, clock) = limiter(2.0, 10);
+        for _ in 0..12 {
+            limiter.acquire().await;
+        }
+        assert_secs(clock.elapsed(), 0.0);
+
+        limiter.acquire().await;
+        assert_secs(clock.elapsed(), 0.5);
+    }
+
+    #[tokio::test]
+    async fn burst_refills_over_the_window() {
+        let (limiter, clock) = limiter(2.0, 10);
+        for _ in 0..12 {
+            limiter.acquire().await;
+        }
+        clock.advance(Duration::from_secs(60));
+        for _ in 0..12 {
+            limiter.acquire().await;
+        }
+        assert_secs(clock.elapsed(), 60.0);
+    }
+
+    #[tokio::test]
+    async fn retry_after_blocks_until_it_passes() {
+        let (limiter, clock) = limiter(2.0, 10);
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("5"));
+
+        limiter
+            .observe(StatusCode::TOO_MANY_REQUESTS, &headers)
+            .await;
+        limiter.acquire().await;
+
+        assert!(clock.elapsed() >= Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn remaining_header_drains_the_burst() {
+        let (limiter, clock) = limiter(2.0, 10);
+        let mut headers = HeaderMap::new();
+        headers.insert(RATE_LIMIT_REMAINING, HeaderValue::from_static("0"));
+
+        limiter.observe(StatusCode::OK, &headers).await;
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+
+        assert_secs(clock.elapsed(), 0.5);
+    }
+
+    #[test]
+    fn retry_after_prefers_the_header_in_seconds() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("1.5"));
+        assert_eq!(
+            retry_after(&headers, Utc::now()),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(retry_after(&HeaderMap::new(), Utc::now()), None);
+    }
+
+    #[test]
+    fn retry_after_counts_down_to_the_reset_from_now() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            RATE_LIMIT_RESET,
+            HeaderValue::from_static("2024-01-01T12:00:10Z"),
+        );
+        let now = DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(retry_after(&headers, now), Some(Duration::from_secs(10)));
+        let later = now + chrono::Duration::seconds(30);
+        assert_eq!(retry_after(&headers, later), None);
+    }
+
+    #[tokio::test]
+    async fn rate_limited_responses_block_until_the_reset_on_the_limiter_clock() {
+        let start = DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let clock = Arc::new(FakeClock::starting_at(start));
+        let limiter = RateLimiter::new(RateLimitConfig::default(), clock.clone());
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            RATE_LIMIT_RESET,
+            HeaderValue::from_static("2024-01-01T12:00:04Z"),
+        );
+
+        limiter
+            .observe(StatusCode::TOO_MANY_REQUESTS, &headers)
+            .await;
+        limiter.acquire().await;
+
+        assert_secs(clock.elapsed(), 4.0);
+    }
+
+    // A fake clock never makes anyone wait, so queueing needs real (paused)
+    // tokio time.
+    #[tokio::test(start_paused = true)]
+    async fn waiting_callers_are_served_in_order() {
+        let clock = Arc::new(TokioClock::starting_at(Utc::now()));
+        let config = RateLimitConfig {
+            per_second: 1.0,
+            burst: 0,
+            burst_window: Duration::from_secs(60),
+        };
+        let limiter = Arc::new(RateLimiter::new(config, clock));
+        limiter.block_for(Duration::from_secs(1)).await;
+
+        let served = Arc::new(StdMutex::new(Vec::new()));
+        let tasks: Vec<_> = (0..5)
+            .map(|caller| {
+                let limiter = limiter.clone();
+                let served = served.clone();
+                tokio::spawn(async move {
+                    limiter.acquire().await;
+                    served.lock().unwrap().push(caller);
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(*served.lock().unwrap(), vec![0, 1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,163 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+use rand::{thread_rng, Rng};
+use reqwest::Method;
+
+use crate::error::SpaceTradersError;
+
+pub type RetryObserver = Arc<dyn Fn(&RetryEvent) + Send + Sync>;
+
+/// What happened to a request that needed (or was refused) another attempt.
+#[derive(Debug)]
+pub enum RetryEvent<'a> {
+    /// `attempt` failed with `error`; the next attempt starts after `delay`.
+    Retrying {
+        method: &'a Method,
+        path: &'a str,
+        attempt: u32,
+        error: &'a SpaceTradersError,
+        delay: Duration,
+    },
+    /// The call succeeded after `attempts` tries.
+    Recovered {
+        method: &'a Method,
+        path: &'a str,
+        attempts: u32,
+    },
+    /// The call failed for good after `attempts` tries.
+    GaveUp {
+        method: &'a Method,
+        path: &'a str,
+        attempts: u32,
+        error: &'a SpaceTradersError,
+    },
+}
+
+impl fmt::Display for RetryEvent<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RetryEvent::Retrying {
+                method,
+                path,
+                attempt,
+                error,
+                delay,
+            } => write!(
+                f,
+                "{} {} failed on attempt {} ({}), retrying in {:?}",
+                method, path, attempt, error, delay
+            ),
+            RetryEvent::Recovered {
+                method,
+                path,
+                attempts,
+            } => write!(
+                f,
+                "{} {} succeeded after {} attempts",
+                method, path, attempts
+            ),
+            RetryEvent::GaveUp {
+                method,
+                path,
+                attempts,
+                error,
+            } => write!(
+                f,
+                "{} {} gave up after {} attempts: {}",
+                method, path, attempts, error
+            ),
+        }
+    }
+}
+
+/// When and how often the client repeats a failed request.
+///
+/// Safe methods are retried on any transient failure. Everything else is
+/// only retried when the failure proves the server never acted on it: a
+/// rate-limit rejection or a connection that was never established.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Fraction of each delay that is randomised, from 0.0 to 1.0.
+    pub jitter: f64,
+    observer: Option<RetryObserver>,
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("base_delay", &self.base_delay)
+            .field("max_delay", &self.max_delay)
+            .field("jitter", &self.jitter)
+            .field("observer", &self.observer.is_some())
+            .finish()
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: 0.5,
+            observer: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_observer(mut self, observer: impl Fn(&RetryEvent) + Send + Sync + 'static) -> Self {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    pub fn notify(&self, event: RetryEvent) {
+        if let Some(observer) = &self.observer {
+            observer(&event);
+        }
+    }
+
+    /// Whether `error` on a request sent with `method` may be attempted again.
+    pub fn is_retryable(&self, method: &Method, error: &SpaceTradersError) -> bool {
+        let idempotent = method.is_safe() || method == Method::PUT || method == Method::DELETE;
+        match error {
+            SpaceTradersError::RateLimited { .. } => true,
+            SpaceTradersError::Http(err) if err.is_connect() => true,
+            _ if !idempotent => false,
+            SpaceTradersError::Http(err) => err.is_timeout() || err.is_request() || err.is_body(),
+            SpaceTradersError::Api { status, .. }
+            | SpaceTradersError::UnexpectedStatus { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+
+    /// Delay before attempt `attempt + 1`, given that `attempt` (1-based) failed.
+    pub fn backoff(&self, attempt: u32, error: &SpaceTradersError) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let scale = 1.0 - jitter + thread_rng().gen::<f64>() * jitter;
+        let delay = exponential.mul_f64(scale);
+
+        match error {
+            SpaceTradersError::RateLimited {
+                retry_after: Some(seconds),
+            } => delay.max(Duration::from_secs_f64(seconds.max(0.0))),
+            _ => delay,
+        }
+    }
+}
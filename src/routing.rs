@@ -0,0 +1,430 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+use crate::models::{ShipNavFlightMode, Waypoint};
+
+/// Flight modes the planner chooses between, in order of preference when
+/// time matters.
+pub const PLANNED_FLIGHT_MODES: [ShipNavFlightMode; 3] = [
+    ShipNavFlightMode::Burn,
+    ShipNavFlightMode::Cruise,
+    ShipNavFlightMode::Drift,
+];
+
+pub fn distance(from: (i32, i32), to: (i32, i32)) -> f64 {
+    let dx = (to.0 - from.0) as f64;
+    let dy = (to.1 - from.1) as f64;
+    (dx * dx + dy * dy).sqrt()
+}
+
+/// Fuel burned flying `distance` in `mode`. Orbitals share coordinates, so
+/// hopping between them is free.
+pub fn fuel_cost(distance: f64, mode: &ShipNavFlightMode) -> u32 {
+    let rounded = distance.round() as u32;
+    if rounded == 0 {
+        return 0;
+    }
+    match mode {
+        ShipNavFlightMode::Drift => 1,
+        ShipNavFlightMode::Burn => (2 * rounded).max(2),
+        _ => rounded.max(1),
+    }
+}
+
+fn speed_multiplier(mode: &ShipNavFlightMode) -> f64 {
+    match mode {
+        ShipNavFlightMode::Burn => 12.5,
+        ShipNavFlightMode::Drift => 250.0,
+        ShipNavFlightMode::Stealth => 30.0,
+        _ => 25.0,
+    }
+}
+
+/// Seconds needed to fly `distance` in `mode` with an engine of `engine_speed`.
+pub fn travel_time(distance: f64, mode: &ShipNavFlightMode, engine_speed: u32) -> u64 {
+    let distance = distance.round().max(1.0);
+    (distance * (speed_multiplier(mode) / engine_speed.max(1) as f64) + 15.0).round() as u64
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteObjective {
+    /// Least travel time, spending fuel freely.
+    Fastest,
+    /// Least fuel burned, however long it takes.
+    Cheapest,
+}
+
+/// The parts of a ship that matter for planning a route.
+#[derive(Debug, Clone, Copy)]
+pub struct ShipSpecs {
+    pub fuel_capacity: u32,
+    pub current_fuel: u32,
+    pub engine_speed: u32,
+}
+
+impl ShipSpecs {
+    pub fn from_ship(ship: &crate::models::Ship) -> Self {
+        ShipSpecs {
+            fuel_capacity: ship.frame.fuel_capacity.unwrap_or(ship.fuel.capacity),
+            current_fuel: ship.fuel.current,
+            engine_speed: ship.engine.speed.unwrap_or(1),
+        }
+    }
+
+    /// Ships without a fuel tank (probes, solar hulls) fly for free.
+    pub fn needs_fuel(&self) -> bool {
+        self.fuel_capacity > 0
+    }
+}
+
+/// A waypoint the planner may route through.
+#[derive(Debug, Clone)]
+pub struct RouteNode {
+    pub symbol: String,
+    pub x: i32,
+    pub y: i32,
+    pub sells_fuel: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteLeg {
+    pub from: String,
+    pub to: String,
+    pub flight_mode: ShipNavFlightMode,
+    /// Refuel at `from` before departing.
+    pub refuel_before: bool,
+    pub distance: f64,
+    pub fuel_cost: u32,
+    pub travel_seconds: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Route {
+    pub legs: Vec<RouteLeg>,
+    pub total_seconds: u64,
+    pub total_fuel: u32,
+}
+
+impl Route {
+    pub fn is_empty(&self) -> bool {
+        self.legs.is_empty()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Frontier {
+    cost: (u64, u64),
+    node: usize,
+}
+
+impl Ord for Frontier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .cmp(&self.cost)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+impl PartialOrd for Frontier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Plans multi-hop routes within one system.
+///
+/// Only fuel stations are worth stopping at: a stop anywhere else costs the
+/// same fuel as flying straight through plus the per-leg overhead. The
+/// planner therefore searches over the origin, every waypoint selling fuel
+/// and the destination, assuming the tank is refilled at each fuel stop.
+#[derive(Debug, Clone, Default)]
+pub struct RoutePlanner {
+    nodes: Vec<RouteNode>,
+}
+
+impl RoutePlanner {
+    pub fn new(nodes: Vec<RouteNode>) -> Self {
+        RoutePlanner { nodes }
+    }
+
+    pub fn from_waypoints(waypoints: &[Waypoint], sells_fuel: impl Fn(&Waypoint) -> bool) -> Self {
+        Self::new(
+            waypoints
+                .iter()
+                .map(|waypoint| RouteNode {
+                    symbol: waypoint.symbol.clone(),
+                    x: waypoint.x,
+                    y: waypoint.y,
+                    sells_fuel: sells_fuel(waypoint),
+                })
+                .collect(),
+        )
+    }
+
+    pub fn nodes(&self) -> &[RouteNode] {
+        &self.nodes
+    }
+
+    fn index_of(&self, symbol: &str) -> Option<usize> {
+        self.nodes.iter().position(|node| node.symbol == symbol)
+    }
+
+    /// Best mode for a single leg given the fuel on board, or `None` if the
+    /// leg can't be flown at all.
+    fn best_leg(
+        &self,
+        distance: f64,
+        available_fuel: u32,
+        ship: &ShipSpecs,
+        objective: RouteObjective,
+    ) -> Option<(ShipNavFlightMode, u32, u64)> {
+        PLANNED_FLIGHT_MODES
+            .iter()
+            .map(|mode| {
+                let fuel = if ship.needs_fuel() {
+                    fuel_cost(distance, mode)
+                } else {
+                    0
+                };
+                (
+                    mode.clone(),
+                    fuel,
+                    travel_time(distance, mode, ship.engine_speed),
+                )
+            })
+            .filter(|(_, fuel, _)| *fuel <= available_fuel || !ship.needs_fuel())
+            .min_by_key(|(_, fuel, seconds)| match objective {
+                RouteObjective::Fastest => (*seconds, *fuel as u64),
+                RouteObjective::Cheapest => (*fuel as u64, *seconds),
+            })
+    }
+
+    /// Plans a route from `from` to `to`, or `None` if either waypoint is
+    /// unknown or the ship can't reach the destination on the fuel available.
+    pub fn plan(
+        &self,
+        from: &str,
+        to: &str,
+        ship: &ShipSpecs,
+        objective: RouteObjective,
+    ) -> Option<Route> {
+        let start = self.index_of(from)?;
+        let goal = self.index_of(to)?;
+        if start == goal {
+            return Some(Route::default());
+        }
+
+        let mut best: HashMap<usize, (u64, u64)> = HashMap::new();
+        let mut previous: HashMap<usize, (usize, RouteLeg)> = HashMap::new();
+        let mut frontier = BinaryHeap::new();
+        best.insert(start, (0, 0));
+        frontier.push(Frontier {
+            cost: (0, 0),
+            node: start,
+        });
+
+        while let Some(Frontier { cost, node }) = frontier.pop() {
+            if node == goal {
+                break;
+            }
+            if best.get(&node).is_some_and(|known| *known < cost) {
+                continue;
+            }
+            let here = &self.nodes[node];
+            let available_fuel = if here.sells_fuel {
+                ship.fuel_capacity
+            } else if node == start {
+                ship.current_fuel
+            } else {
+                continue;
+            };
+
+            for (next, there) in self.nodes.iter().enumerate() {
+                if next == node || !(there.sells_fuel || next == goal) {
+                    continue;
+                }
+                let distance = distance((here.x, here.y), (there.x, there.y));
+                let Some((mode, fuel, seconds)) =
+                    self.best_leg(distance, available_fuel, ship, objective)
+                else {
+                    continue;
+                };
+                let step = match objective {
+                    RouteObjective::Fastest => (seconds, fuel as u64),
+                    RouteObjective::Cheapest => (fuel as u64, seconds),
+                };
+                let next_cost = (cost.0 + step.0, cost.1 + step.1);
+                if best.get(&next).is_some_and(|known| *known <= next_cost) {
+                    continue;
+                }
+                best.insert(next, next_cost);
+                previous.insert(
+                    next,
+                    (
+                        node,
+                        RouteLeg {
+                            from: here.symbol.clone(),
+                            to: there.symbol.clone(),
+                            flight_mode: mode,
+                            refuel_before: here.sells_fuel
+                                && ship.needs_fuel()
+                                && (node != start || ship.current_fuel < ship.fuel_capacity),
+                            distance,
+                            fuel_cost: fuel,
+                            travel_seconds: seconds,
+                        },
+                    ),
+                );
+                frontier.push(Frontier {
+                    cost: next_cost,
+                    node: next,
+                });
+            }
+        }
+
+        let mut legs = Vec::new();
+        let mut node = goal;
+        while node != start {
+            let (prev, leg) = previous.remove(&node)?;
+            legs.push(leg);
+            node = prev;
+        }
+        legs.reverse();
+
+        Some(Route {
+            total_seconds: legs.iter().map(|leg| leg.travel_seconds).sum(),
+            total_fuel: legs.iter().map(|leg| leg.fuel_cost).sum(),
+            legs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPECS: ShipSpecs = ShipSpecs {
+        fuel_capacity: 60,
+        current_fuel: 60,
+        engine_speed: 30,
+    };
+
+    fn node(symbol: &str, x: i32, sells_fuel: bool) -> RouteNode {
+        RouteNode {
+            symbol: symbol.to_string(),
+            x,
+            y: 0,
+            sells_fuel,
+        }
+    }
+
+    /// Start and goal 100 apart with a fuel station halfway between them.
+    fn planner() -> RoutePlanner {
+        RoutePlanner::new(vec![
+            node("START", 0, false),
+            node("FUEL", 50, true),
+            node("GOAL", 100, false),
+        ])
+    }
+
+    #[test]
+    fn fuel_cost_by_mode() {
+        assert_eq!(fuel_cost(10.0, &ShipNavFlightMode::Cruise), 10);
+        assert_eq!(fuel_cost(10.0, &ShipNavFlightMode::Burn), 20);
+        assert_eq!(fuel_cost(10.0, &ShipNavFlightMode::Drift), 1);
+        assert_eq!(fuel_cost(0.6, &ShipNavFlightMode::Burn), 2);
+    }
+
+    #[test]
+    fn hops_between_orbitals_are_free() {
+        for mode in PLANNED_FLIGHT_MODES {
+            assert_eq!(fuel_cost(0.0, &mode), 0);
+            assert_eq!(fuel_cost(0.4, &mode), 0);
+        }
+    }
+
+    #[test]
+    fn travel_time_by_mode() {
+        assert_eq!(travel_time(10.0, &ShipNavFlightMode::Cruise, 30), 23);
+        assert_eq!(travel_time(10.0, &ShipNavFlightMode::Burn, 30), 19);
+        assert_eq!(travel_time(10.0, &ShipNavFlightMode::Drift, 30), 98);
+        // Even a hop between orbitals takes the fixed overhead.
+        assert_eq!(travel_time(0.0, &ShipNavFlightMode::Cruise, 30), 16);
+    }
+
+    #[test]
+    fn plan_stops_to_refuel_when_faster() {
+        let route = planner()
+            .plan("START", "GOAL", &SPECS, RouteObjective::Fastest)
+            .unwrap();
+
+        let stops: Vec<(&str, &str)> = route
+            .legs
+            .iter()
+            .map(|leg| (leg.from.as_str(), leg.to.as_str()))
+            .collect();
+        assert_eq!(stops, vec![("START", "FUEL"), ("FUEL", "GOAL")]);
+        assert!(!route.legs[0].refuel_before);
+        assert!(route.legs[1].refuel_before);
+        assert!(route
+            .legs
+            .iter()
+            .all(|leg| leg.flight_mode == ShipNavFlightMode::Cruise));
+        assert_eq!(route.total_fuel, 100);
+        assert_eq!(route.total_seconds, 114);
+    }
+
+    #[test]
+    fn cheapest_plan_drifts_straight_there() {
+        let route = planner()
+            .plan("START", "GOAL", &SPECS, RouteObjective::Cheapest)
+            .unwrap();
+
+        assert_eq!(route.legs.len(), 1);
+        assert_eq!(route.legs[0].flight_mode, ShipNavFlightMode::Drift);
+        assert_eq!(route.total_fuel, 1);
+    }
+
+    #[test]
+    fn plan_is_none_without_the_fuel_to_leave() {
+        let empty = ShipSpecs {
+            current_fuel: 0,
+            ..SPECS
+        };
+        for objective in [RouteObjective::Fastest, RouteObjective::Cheapest] {
+            assert_eq!(planner().plan("START", "GOAL", &empty, objective), None);
+        }
+    }
+
+    #[test]
+    fn ships_without_a_tank_fly_anywhere() {
+        let probe = ShipSpecs {
+            fuel_capacity: 0,
+            current_fuel: 0,
+            engine_speed: 30,
+        };
+        let route = planner()
+            .plan("START", "GOAL", &probe, RouteObjective::Fastest)
+            .unwrap();
+
+        assert_eq!(route.legs.len(), 1);
+        assert_eq!(route.legs[0].flight_mode, ShipNavFlightMode::Burn);
+        assert_eq!(route.total_fuel, 0);
+    }
+
+    #[test]
+    fn plan_to_unknown_or_same_waypoint() {
+        let planner = planner();
+        assert_eq!(
+            planner.plan("START", "NOWHERE", &SPECS, RouteObjective::Fastest),
+            None
+        );
+        assert_eq!(
+            planner.plan("GOAL", "GOAL", &SPECS, RouteObjective::Fastest),
+            Some(Route::default())
+        );
+    }
+}
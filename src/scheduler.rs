@@ -0,0 +1,137 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::client::ApiClient;
+use crate::clock::Clock;
+use crate::error::Result;
+use crate::models::{Cooldown, Ship, ShipNav, ShipNavStatus};
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ShipTimers {
+    cooldown_until: Option<DateTime<Utc>>,
+    arrival_at: Option<DateTime<Utc>>,
+}
+
+/// Tracks when each ship can next act, from the cooldowns and arrival times
+/// the API hands back, so ship tasks can sleep exactly that long instead of
+/// spending a request to find out they are still busy.
+///
+/// One scheduler is shared by every ship task; each task only ever waits on
+/// its own ship, so any number of ships can run side by side on one runtime.
+#[derive(Debug)]
+pub struct Scheduler {
+    clock: Arc<dyn Clock>,
+    timers: Mutex<HashMap<String, ShipTimers>>,
+}
+
+impl Scheduler {
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Scheduler {
+            clock,
+            timers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    fn update(&self, ship_symbol: &str, update: impl FnOnce(&mut ShipTimers)) {
+        let mut timers = self.timers.lock().unwrap();
+        update(timers.entry(ship_symbol.to_string()).or_default());
+    }
+
+    fn timers(&self, ship_symbol: &str) -> ShipTimers {
+        self.timers
+            .lock()
+            .unwrap()
+            .get(ship_symbol)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Drops times that have already passed.
+    fn pending(&self, time: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+        time.filter(|time| *time > self.clock.utc_now())
+    }
+
+    pub fn record_cooldown(&self, cooldown: &Cooldown) {
+        let until = match cooldown.expiration {
+            _ if cooldown.remaining_seconds == 0 => None,
+            Some(expiration) => Some(expiration),
+            None => Some(
+                self.clock.utc_now() + chrono::Duration::seconds(cooldown.remaining_seconds as i64),
+            ),
+        };
+        self.update(&cooldown.ship_symbol, |timers| {
+            timers.cooldown_until = until
+        });
+    }
+
+    /// Records a cooldown reported only as a number of seconds, as in the
+    /// error returned when acting too early.
+    pub fn record_cooldown_seconds(&self, ship_symbol: &str, remaining_seconds: u64) {
+        let until = self.clock.utc_now() + chrono::Duration::seconds(remaining_seconds as i64);
+        self.update(ship_symbol, |timers| timers.cooldown_until = Some(until));
+    }
+
+    pub fn record_nav(&self, ship_symbol: &str, nav: &ShipNav) {
+        let arrival = (nav.status == ShipNavStatus::InTransit).then_some(nav.route.arrival);
+        self.update(ship_symbol, |timers| timers.arrival_at = arrival);
+    }
+
+    pub fn observe_ship(&self, ship: &Ship) {
+        self.record_nav(&ship.symbol, &ship.nav);
+        self.record_cooldown(&ship.cooldown);
+    }
+
+    /// Refreshes the ship's cooldown from `GET /my/ships/{ship}/cooldown`.
+    pub async fn sync_cooldown(&self, client: &ApiClient, ship_symbol: &str) -> Result<()> {
+        match client.get_ship_cooldown(ship_symbol).await? {
+            Some(cooldown) => self.record_cooldown(&cooldown),
+            None => self.update(ship_symbol, |timers| timers.cooldown_until = None),
+        }
+        Ok(())
+    }
+
+    pub fn cooldown_until(&self, ship_symbol: &str) -> Option<DateTime<Utc>> {
+        self.pending(self.timers(ship_symbol).cooldown_until)
+    }
+
+    pub fn arrival_at(&self, ship_symbol: &str) -> Option<DateTime<Utc>> {
+        self.pending(self.timers(ship_symbol).arrival_at)
+    }
+
+    /// When the ship has both landed and come off cooldown, or `None` if it
+    /// can act right away.
+    pub fn ready_at(&self, ship_symbol: &str) -> Option<DateTime<Utc>> {
+        let timers = self.timers(ship_symbol);
+        self.pending(timers.cooldown_until.max(timers.arrival_at))
+    }
+
+    /// The ship that will be ready soonest among those still busy.
+    pub fn next_ready(&self) -> Option<(String, DateTime<Utc>)> {
+        let ships: Vec<String> = self.timers.lock().unwrap().keys().cloned().collect();
+        ships
+            .into_iter()
+            .filter_map(|ship| self.ready_at(&ship).map(|time| (ship, time)))
+            .min_by_key(|(_, time)| *time)
+    }
+
+    pub async fn wait_for_arrival(&self, ship_symbol: &str) {
+        if let Some(arrival) = self.arrival_at(ship_symbol) {
+            self.clock.sleep_until_utc(arrival).await;
+        }
+    }
+
+    /// Sleeps until the ship can extract, survey or otherwise act again.
+    pub async fn wait_until_ready(&self, ship_symbol: &str) {
+        if let Some(ready) = self.ready_at(ship_symbol) {
+            self.clock.sleep_until_utc(ready).await;
+        }
+    }
+}
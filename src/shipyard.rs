@@ -0,0 +1,97 @@
+use crate::client::ApiClient;
+use crate::error::Result;
+use crate::fleet::FleetController;
+use crate::models::{Ship, ShipType};
+
+/// A ship for sale at a shipyard, at the price last quoted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShipOffer {
+    pub shipyard: String,
+    pub ship_type: ShipType,
+    pub price: u64,
+}
+
+/// Buys the ships the fleet controller asks for at the cheapest shipyard in
+/// the system, never letting credits drop below `credit_reserve`.
+#[derive(Debug, Clone, Default)]
+pub struct PurchasePlanner {
+    pub credit_reserve: u64,
+}
+
+impl PurchasePlanner {
+    pub fn new(credit_reserve: u64) -> Self {
+        PurchasePlanner { credit_reserve }
+    }
+
+    /// Every priced offer for `ship_type` in `system`, cheapest first.
+    /// Shipyards only quote prices while one of our ships is there.
+    pub async fn offers(
+        &self,
+        client: &ApiClient,
+        system: &str,
+        ship_type: &ShipType,
+    ) -> Result<Vec<ShipOffer>> {
+        let mut offers = Vec::new();
+        for shipyard in client.find_shipyards(system).await? {
+            let available = client
+                .find_ships_at_shipyard(system, &shipyard.symbol)
+                .await?;
+            for ship in available.ships.unwrap_or_default() {
+                if let (Some(offered), Some(price)) = (ship.ship_type, ship.purchase_price) {
+                    if &offered == ship_type {
+                        offers.push(ShipOffer {
+                            shipyard: shipyard.symbol.clone(),
+                            ship_type: offered,
+                            price,
+                        });
+                    }
+                }
+            }
+        }
+        offers.sort_by_key(|offer| offer.price);
+        Ok(offers)
+    }
+
+    pub fn can_afford(&self, credits: u64, price: u64) -> bool {
+        credits >= price.saturating_add(self.credit_reserve)
+    }
+
+    /// Buys the next ship the controller needs if the cheapest offer fits
+    /// the budget, and hands it to the controller. Returns the new ship.
+    pub async fn buy_needed(&self, controller: &mut FleetController) -> Result<Option<Ship>> {
+        let Some(target) = controller.needed_purchase().cloned() else {
+            return Ok(None);
+        };
+        let client = controller.context().client().clone();
+        let system = controller.context().home_system.clone();
+        let Some(offer) = self
+            .offers(&client, &system, &target.ship_type)
+            .await?
+            .into_iter()
+            .next()
+        else {
+            println!("No shipyard in {} is quoting {}", system, target.ship_type);
+            return Ok(None);
+        };
+        let credits = client.get_agent_data().await?.credits;
+        if !self.can_afford(credits, offer.price) {
+            println!(
+                "Saving up for {} at {}: {} credits, need {} plus {} reserve",
+                offer.ship_type, offer.shipyard, credits, offer.price, self.credit_reserve
+            );
+            return Ok(None);
+        }
+
+        let purchase = client.buy_ship(&offer.shipyard, &offer.ship_type).await?;
+        println!(
+            "Bought {} {} at {} for {}, {} credits left",
+            offer.ship_type,
+            purchase.ship.symbol,
+            offer.shipyard,
+            offer.price,
+            purchase.agent.credits
+        );
+        controller.register_purchase(purchase.ship.clone(), target.behaviour);
+        Ok(Some(purchase.ship))
+    }
+}
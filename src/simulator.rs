@@ -0,0 +1,217 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+use chrono::Utc;
+
+use crate::clock::{Clock, TokioClock};
+use crate::contract_evaluator::ContractEvaluator;
+use crate::error::Result;
+use crate::fleet::{Behaviour, FleetConfig, FleetContext, FleetController, PurchaseTarget};
+use crate::market_store::MarketStore;
+use crate::mining::MiningConfig;
+use crate::mock_server::{MockConfig, MockServer};
+use crate::models::{ShipRole, ShipType};
+use crate::navigation::Navigator;
+use crate::rate_limit::{RateLimitConfig, RateLimiter};
+use crate::shipyard::PurchasePlanner;
+use crate::survey_cache::SurveyCache;
+
+/// How the simulated fleet makes its money.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Every ship mines and sells what it extracts.
+    Mining,
+    /// Every ship trades between markets.
+    Trading,
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Strategy::Mining => "mining",
+            Strategy::Trading => "trading",
+        })
+    }
+}
+
+impl Strategy {
+    /// The command ship and every ship bought follow the strategy; mining
+    /// fleets grow with drones, trading fleets with light haulers.
+    pub fn fleet_config(&self) -> FleetConfig {
+        let (behaviour, ship_type) = match self {
+            Strategy::Mining => (Behaviour::Miner, ShipType::ShipMiningDrone),
+            Strategy::Trading => (Behaviour::Trader, ShipType::ShipLightHauler),
+        };
+        let mut config = FleetConfig::default();
+        config.roles.insert(ShipRole::Command, behaviour);
+        config.purchases = vec![PurchaseTarget {
+            ship_type,
+            behaviour,
+            count: 4,
+        }];
+        config
+    }
+}
+
+/// One simulated run.
+#[derive(Debug, Clone)]
+pub struct SimConfig {
+    pub strategy: Strategy,
+    /// Picks the generated system, yields and contracts. Runs with the same
+    /// seed play out in the same world.
+    pub seed: u64,
+    /// Game time to simulate.
+    pub duration: Duration,
+    pub credit_reserve: u64,
+    /// The game's rules. The seed and clock are set by the simulator.
+    pub game: MockConfig,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        SimConfig {
+            strategy: Strategy::Mining,
+            seed: 1,
+            duration: Duration::from_secs(3 * 24 * 3600),
+            credit_reserve: 25_000,
+            game: MockConfig {
+                market_elasticity: 0.15,
+                ..MockConfig::default()
+            },
+        }
+    }
+}
+
+/// How a simulated run ended.
+#[derive(Debug, Clone)]
+pub struct SimReport {
+    pub strategy: Strategy,
+    pub seed: u64,
+    pub simulated: Duration,
+    pub starting_credits: u64,
+    pub final_credits: u64,
+    pub ships: u64,
+    /// Net credits from buying and selling goods, fuel included.
+    pub trade_income: i64,
+    pub requests: usize,
+}
+
+impl SimReport {
+    pub fn profit(&self) -> i64 {
+        self.final_credits as i64 - self.starting_credits as i64
+    }
+
+    pub fn credits_per_hour(&self) -> f64 {
+        let hours = self.simulated.as_secs_f64() / 3600.0;
+        if hours == 0.0 {
+            return 0.0;
+        }
+        self.profit() as f64 / hours
+    }
+}
+
+impl fmt::Display for SimReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (seed {}): {} -> {} credits ({:+}, {:.0}/h) over {:.1}h, \
+            {} ships, trade income {}, {} requests",
+            self.strategy,
+            self.seed,
+            self.starting_credits,
+            self.final_credits,
+            self.profit(),
+            self.credits_per_hour(),
+            self.simulated.as_secs_f64() / 3600.0,
+            self.ships,
+            self.trade_income,
+            self.requests
+        )
+    }
+}
+
+/// Runs the fleet automation against a generated game for
+/// `config.duration` of game time and reports how it did.
+///
+/// The game and the bot share one virtual clock on a paused tokio runtime,
+/// so time only passes while every ship is waiting, and days play out in
+/// seconds. Requests still go through the real client, rate limit included.
+/// Builds its own runtime, so call it from outside async code (or from
+/// `spawn_blocking`).
+pub fn simulate(config: &SimConfig) -> Result<SimReport> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .start_paused(true)
+        .build()?;
+    runtime.block_on(run(config))
+}
+
+/// Simulates each strategy in the same world.
+pub fn compare(config: &SimConfig, strategies: &[Strategy]) -> Result<Vec<SimReport>> {
+    strategies
+        .iter()
+        .map(|strategy| {
+            simulate(&SimConfig {
+                strategy: *strategy,
+                ..config.clone()
+            })
+        })
+        .collect()
+}
+
+async fn run(config: &SimConfig) -> Result<SimReport> {
+    let clock = Arc::new(TokioClock::starting_at(Utc::now()));
+    let server = MockServer::start(MockConfig {
+        seed: Some(config.seed),
+        clock: clock.clone(),
+        ..config.game.clone()
+    })
+    .await?;
+    let limiter = RateLimiter::new(RateLimitConfig::default(), clock.clone());
+    let client = server.client().with_rate_limiter(Arc::new(limiter));
+    let system = server.state().world().system.clone();
+
+    // Start from a price survey of every market, as a scouting probe would
+    // have provided; traders have nothing to go on otherwise.
+    let market_store = MarketStore::in_memory().shared();
+    {
+        let state = server.state();
+        let mut store = market_store.lock().unwrap();
+        for market in state.world().markets.values() {
+            store.record_market_at(market, clock.utc_now())?;
+        }
+    }
+
+    let navigator = Navigator::new(
+        client.clone(),
+        Arc::new(client.route_planner(&system).await?),
+    );
+    let context = FleetContext {
+        navigator,
+        market_store: market_store.clone(),
+        home_system: system,
+        mining: MiningConfig::default(),
+        surveys: SurveyCache::new().shared(),
+        evaluator: ContractEvaluator::default(),
+        credit_reserve: config.credit_reserve,
+    };
+    let starting_credits = server.state().agent().credits;
+    let mut controller = FleetController::new(context, config.strategy.fleet_config())
+        .with_purchase_planner(PurchasePlanner::new(config.credit_reserve));
+    if let Ok(Err(err)) = tokio::time::timeout(config.duration, controller.run()).await {
+        return Err(err);
+    }
+    drop(controller);
+
+    let trade_income = market_store.lock().unwrap().net_trade_income();
+    let state = server.state();
+    Ok(SimReport {
+        strategy: config.strategy,
+        seed: config.seed,
+        simulated: clock.elapsed(),
+        starting_credits,
+        final_credits: state.agent().credits,
+        ships: state.agent().ship_count,
+        trade_income,
+        requests: server.requests().len(),
+    })
+}
@@ -0,0 +1,250 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{Survey, SurveySize, TradeSymbol};
+
+pub type SharedSurveyCache = Arc<Mutex<SurveyCache>>;
+
+/// How many surveys are kept per waypoint; the ones expiring soonest go first.
+pub const MAX_SURVEYS_PER_WAYPOINT: usize = 20;
+
+fn size_rank(size: &SurveySize) -> u8 {
+    match size {
+        SurveySize::Large => 3,
+        SurveySize::Moderate => 2,
+        SurveySize::Small => 1,
+        SurveySize::Unknown(_) => 0,
+    }
+}
+
+/// Orders surveys by how much of `desired` they yield, then by size (larger
+/// surveys last more extractions), then by how long they stay valid.
+pub fn compare_surveys(a: &Survey, b: &Survey, desired: &[TradeSymbol]) -> Ordering {
+    a.desired_ratio(desired)
+        .total_cmp(&b.desired_ratio(desired))
+        .then_with(|| size_rank(&a.size).cmp(&size_rank(&b.size)))
+        .then_with(|| a.expiration.cmp(&b.expiration))
+}
+
+/// Surveys shared between surveyors and miners, grouped by waypoint.
+/// Expired surveys are dropped on access and exhausted ones are discarded
+/// when an extraction reports them unusable.
+#[derive(Debug, Default)]
+pub struct SurveyCache {
+    surveys: HashMap<String, Vec<Survey>>,
+}
+
+impl SurveyCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn shared(self) -> SharedSurveyCache {
+        Arc::new(Mutex::new(self))
+    }
+
+    pub fn insert(&mut self, surveys: impl IntoIterator<Item = Survey>) {
+        for survey in surveys {
+            let cached = self.surveys.entry(survey.symbol.clone()).or_default();
+            if cached
+                .iter()
+                .any(|known| known.signature == survey.signature)
+            {
+                continue;
+            }
+            cached.push(survey);
+            if cached.len() > MAX_SURVEYS_PER_WAYPOINT {
+                cached.sort_by_key(|survey| std::cmp::Reverse(survey.expiration));
+                cached.truncate(MAX_SURVEYS_PER_WAYPOINT);
+            }
+        }
+    }
+
+    pub fn discard(&mut self, signature: &str) {
+        for cached in self.surveys.values_mut() {
+            cached.retain(|survey| survey.signature != signature);
+        }
+    }
+
+    /// Drops every survey that has expired by `now`.
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        for cached in self.surveys.values_mut() {
+            cached.retain(|survey| !survey.is_expired(now));
+        }
+        self.surveys.retain(|_, cached| !cached.is_empty());
+    }
+
+    pub fn surveys(&self, waypoint_symbol: &str) -> &[Survey] {
+        self.surveys
+            .get(waypoint_symbol)
+            .map_or(&[], |cached| cached.as_slice())
+    }
+
+    pub fn len(&self) -> usize {
+        self.surveys.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The unexpired survey of `waypoint_symbol` most likely to yield
+    /// `desired`, or `None` if no survey has any of it.
+    pub fn best(
+        &mut self,
+        waypoint_symbol: &str,
+        desired: &[TradeSymbol],
+        now: DateTime<Utc>,
+    ) -> Option<Survey> {
+        self.prune(now);
+        self.surveys(waypoint_symbol)
+            .iter()
+            .filter(|survey| survey.desired_ratio(desired) > 0.0)
+            .max_by(|a, b| compare_surveys(a, b, desired))
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::models::SurveyDeposit;
+
+    const ASTEROID: &str = "X1-TEST-B1";
+
+    fn survey(
+        signature: &str,
+        deposits: &[TradeSymbol],
+        size: SurveySize,
+        expiration: DateTime<Utc>,
+    ) -> Survey {
+        Survey {
+            signature: signature.to_string(),
+            symbol: ASTEROID.to_string(),
+            deposits: deposits
+                .iter()
+                .map(|symbol| SurveyDeposit {
+                    symbol: symbol.clone(),
+                })
+                .collect(),
+            expiration,
+            size,
+        }
+    }
+
+    fn signature(survey: Option<Survey>) -> Option<String> {
+        survey.map(|survey| survey.signature)
+    }
+
+    #[test]
+    fn best_prefers_the_highest_ratio_of_desired_goods() {
+        use TradeSymbol::*;
+        let now = Utc::now();
+        let later = now + Duration::minutes(10);
+        let mut cache = SurveyCache::new();
+        cache.insert([
+            survey(
+                "LOW",
+                &[IronOre, QuartzSand, QuartzSand],
+                SurveySize::Large,
+                later,
+            ),
+            survey(
+                "HIGH",
+                &[IronOre, IronOre, QuartzSand],
+                SurveySize::Small,
+                later,
+            ),
+            survey("NONE", &[QuartzSand], SurveySize::Large, later),
+        ]);
+
+        assert_eq!(
+            signature(cache.best(ASTEROID, &[IronOre], now)),
+            Some("HIGH".to_string())
+        );
+        assert_eq!(
+            signature(cache.best(ASTEROID, &[QuartzSand], now)),
+            Some("NONE".to_string())
+        );
+        assert_eq!(cache.best(ASTEROID, &[GoldOre], now), None);
+    }
+
+    #[test]
+    fn equal_ratios_fall_back_to_size_then_expiration() {
+        use TradeSymbol::IronOre;
+        let now = Utc::now();
+        let soon = now + Duration::minutes(5);
+        let later = now + Duration::minutes(10);
+        let small = survey("SMALL", &[IronOre], SurveySize::Small, later);
+        let large = survey("LARGE", &[IronOre], SurveySize::Large, soon);
+        let large_later = survey("LARGE-LATER", &[IronOre], SurveySize::Large, later);
+
+        assert_eq!(
+            compare_surveys(&large, &small, &[IronOre]),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare_surveys(&large_later, &large, &[IronOre]),
+            Ordering::Greater
+        );
+
+        let mut cache = SurveyCache::new();
+        cache.insert([small, large, large_later]);
+        assert_eq!(
+            signature(cache.best(ASTEROID, &[IronOre], now)),
+            Some("LARGE-LATER".to_string())
+        );
+    }
+
+    #[test]
+    fn expired_surveys_are_pruned() {
+        use TradeSymbol::IronOre;
+        let now = Utc::now();
+        let mut cache = SurveyCache::new();
+        cache.insert([
+            survey("EXPIRED", &[IronOre], SurveySize::Large, now),
+            survey(
+                "VALID",
+                &[IronOre],
+                SurveySize::Small,
+                now + Duration::minutes(1),
+            ),
+        ]);
+
+        assert_eq!(
+            signature(cache.best(ASTEROID, &[IronOre], now)),
+            Some("VALID".to_string())
+        );
+        assert_eq!(cache.len(), 1);
+
+        assert_eq!(
+            cache.best(ASTEROID, &[IronOre], now + Duration::minutes(1)),
+            None
+        );
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn duplicates_are_ignored_and_discarded_surveys_dropped() {
+        use TradeSymbol::IronOre;
+        let later = Utc::now() + Duration::minutes(10);
+        let mut cache = SurveyCache::new();
+        cache.insert([
+            survey("ONE", &[IronOre], SurveySize::Small, later),
+            survey("ONE", &[IronOre], SurveySize::Small, later),
+            survey("TWO", &[IronOre], SurveySize::Small, later),
+        ]);
+        assert_eq!(cache.len(), 2);
+
+        cache.discard("ONE");
+        assert_eq!(cache.surveys(ASTEROID).len(), 1);
+        assert_eq!(cache.surveys(ASTEROID)[0].signature, "TWO");
+    }
+}
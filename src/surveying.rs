@@ -0,0 +1,40 @@
+use crate::error::{Result, SpaceTradersError};
+use crate::fleet::FleetContext;
+use crate::mining::home_asteroid;
+use crate::models::Ship;
+
+/// Surveys the home asteroid whenever the ship's cooldown allows, feeding the
+/// shared survey cache that miners extract with.
+pub async fn run(context: FleetContext, mut surveyor: Ship) -> Result<()> {
+    let Some(asteroid) = home_asteroid(&context).await? else {
+        println!("No engineered asteroid in {}", context.home_system);
+        return Ok(());
+    };
+    let navigator = &context.navigator;
+    let scheduler = navigator.scheduler();
+    let client = context.client();
+    scheduler.sync_cooldown(client, &surveyor.symbol).await?;
+    navigator.go_to(&mut surveyor, &asteroid).await?;
+    loop {
+        navigator.ensure_in_orbit(&mut surveyor).await?;
+        scheduler.wait_until_ready(&surveyor.symbol).await;
+        match client.create_survey(&surveyor.symbol).await {
+            Err(SpaceTradersError::Cooldown { remaining_seconds }) => {
+                scheduler.record_cooldown_seconds(&surveyor.symbol, remaining_seconds);
+            }
+            Err(err) => return Err(err),
+            Ok(result) => {
+                scheduler.record_cooldown(&result.cooldown);
+                println!(
+                    "{} surveyed {}: {} surveys",
+                    surveyor.symbol,
+                    asteroid,
+                    result.surveys.len()
+                );
+                let mut surveys = context.surveys.lock().unwrap();
+                surveys.insert(result.surveys);
+                surveys.prune(client.clock().utc_now());
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::time::Duration;
+
+use crate::contracts::units_held;
+use crate::error::Result;
+use crate::fleet::FleetContext;
+use crate::models::{Market, Ship, TradeSymbol};
+
+/// How long a trader waits before looking again when no trade pays.
+const IDLE_WAIT: Duration = Duration::from_secs(300);
+
+/// Buys goods where the market store says they are cheapest and sells them
+/// where they fetch the most, repeating for as long as a trade pays. Never
+/// spends credits below the context's reserve.
+pub async fn run(context: FleetContext, mut trader: Ship) -> Result<()> {
+    let navigator = &context.navigator;
+    let client = context.client();
+    loop {
+        navigator.wait_for_arrival(&mut trader).await?;
+        let opportunity = context
+            .market_store
+            .lock()
+            .unwrap()
+            .trade_opportunities(&trader.nav.system_symbol)
+            .into_iter()
+            .next();
+        let Some(opportunity) = opportunity else {
+            println!("{} found no profitable trade, waiting", trader.symbol);
+            client.clock().sleep(IDLE_WAIT).await;
+            continue;
+        };
+        println!(
+            "{} trading {}: buy at {} for {}, sell at {} for {}",
+            trader.symbol,
+            opportunity.symbol,
+            opportunity.buy.waypoint_symbol,
+            opportunity.buy.purchase_price,
+            opportunity.sell.waypoint_symbol,
+            opportunity.sell.sell_price
+        );
+
+        navigator
+            .go_to(&mut trader, &opportunity.buy.waypoint_symbol)
+            .await?;
+        navigator.ensure_docked(&mut trader).await?;
+        navigator.refuel(&mut trader).await?;
+        let market = record_market(&context, &trader).await?;
+        clear_hold(&context, &mut trader, &market, &opportunity.symbol).await?;
+        let price = market
+            .trade_good(&opportunity.symbol)
+            .map_or(opportunity.buy.purchase_price, |good| good.purchase_price)
+            .max(1);
+        let credits = client.get_agent_data().await?.credits;
+        let affordable = credits.saturating_sub(context.credit_reserve) / price;
+        let space = trader.cargo.capacity.saturating_sub(trader.cargo.units);
+        let units = space
+            .min(opportunity.buy.trade_volume)
+            .min(affordable.min(u32::MAX as u64) as u32);
+        if units == 0 && units_held(&trader, &opportunity.symbol) == 0 {
+            if affordable == 0 {
+                println!(
+                    "{} can't buy {} without dipping into the {} credit reserve, waiting",
+                    trader.symbol, opportunity.symbol, context.credit_reserve
+                );
+            } else {
+                println!(
+                    "{} has nothing to trade in {}, waiting",
+                    trader.symbol, opportunity.symbol
+                );
+            }
+            client.clock().sleep(IDLE_WAIT).await;
+            continue;
+        }
+        if units > 0 {
+            let purchase = client
+                .purchase_goods(&trader.symbol, &opportunity.symbol, units)
+                .await?;
+            context
+                .market_store
+                .lock()
+                .unwrap()
+                .record_transaction(&purchase.transaction)?;
+            trader.cargo = purchase.cargo;
+        }
+
+        navigator
+            .go_to(&mut trader, &opportunity.sell.waypoint_symbol)
+            .await?;
+        navigator.ensure_docked(&mut trader).await?;
+        navigator.refuel(&mut trader).await?;
+        let market = record_market(&context, &trader).await?;
+        let trade_volume = market
+            .trade_good(&opportunity.symbol)
+            .map_or(u32::MAX, |good| good.trade_volume.max(1));
+        sell_all(&context, &mut trader, &opportunity.symbol, trade_volume).await?;
+    }
+}
+
+/// Makes room for `keep` by selling everything else in the hold to `market`,
+/// and jettisoning what it doesn't buy.
+async fn clear_hold(
+    context: &FleetContext,
+    ship: &mut Ship,
+    market: &Market,
+    keep: &TradeSymbol,
+) -> Result<()> {
+    let others: Vec<(TradeSymbol, u32)> = ship
+        .cargo
+        .inventory
+        .iter()
+        .filter(|cargo| &cargo.symbol != keep && cargo.units > 0)
+        .map(|cargo| (cargo.symbol.clone(), cargo.units))
+        .collect();
+    for (symbol, units) in others {
+        match market.trade_good(&symbol) {
+            Some(good) => sell_all(context, ship, &symbol, good.trade_volume.max(1)).await?,
+            None => {
+                ship.cargo = context
+                    .client()
+                    .jettison_cargo(&ship.symbol, &symbol, units)
+                    .await?;
+                println!("{} jettisoned {} {}", ship.symbol, units, symbol);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Fetches the market where `ship` is and records its prices.
+pub async fn record_market(context: &FleetContext, ship: &Ship) -> Result<Market> {
+    let client = context.client();
+    let market = client
+        .get_market(&ship.nav.system_symbol, &ship.nav.waypoint_symbol)
+        .await?;
+    let observed_at = client.clock().utc_now();
+    context
+        .market_store
+        .lock()
+        .unwrap()
+        .record_market_at(&market, observed_at)?;
+    Ok(market)
+}
+
+/// Sells every unit of `symbol` in the hold, `trade_volume` units at a time.
+pub async fn sell_all(
+    context: &FleetContext,
+    ship: &mut Ship,
+    symbol: &TradeSymbol,
+    trade_volume: u32,
+) -> Result<()> {
+    sell_units(context, ship, symbol, u32::MAX, trade_volume).await
+}
+
+/// Sells up to `units` of `symbol` from the hold, `trade_volume` units at a
+/// time.
+pub async fn sell_units(
+    context: &FleetContext,
+    ship: &mut Ship,
+    symbol: &TradeSymbol,
+    units: u32,
+    trade_volume: u32,
+) -> Result<()> {
+    let mut remaining = units;
+    while remaining > 0 {
+        let held = ship
+            .cargo
+            .inventory
+            .iter()
+            .find(|cargo| &cargo.symbol == symbol)
+            .map_or(0, |cargo| cargo.units);
+        if held == 0 {
+            return Ok(());
+        }
+        let sale = context
+            .client()
+            .sell_goods(&ship.symbol, symbol, held.min(trade_volume).min(remaining))
+            .await?;
+        context
+            .market_store
+            .lock()
+            .unwrap()
+            .record_transaction(&sale.transaction)?;
+        println!(
+            "{} sold {} {} for {}",
+            ship.symbol,
+            sale.transaction.units,
+            sale.transaction.trade_symbol,
+            sale.transaction.total_price
+        );
+        remaining = remaining.saturating_sub(sale.transaction.units);
+        ship.cargo = sale.cargo;
+    }
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use futures::Stream;
+
+use crate::client::ApiClient;
+use crate::error::Result;
+use crate::models::{
+    GetSystemResponse, GetWaypointResponse, System, Waypoint, WaypointTraitSymbol, WaypointType,
+};
+
+/// Filters for `GET /systems/{system}/waypoints`. The API ANDs the waypoint
+/// type with every requested trait.
+#[derive(Debug, Clone)]
+pub struct WaypointQuery {
+    system: String,
+    waypoint_type: Option<WaypointType>,
+    traits: Vec<WaypointTraitSymbol>,
+}
+
+impl WaypointQuery {
+    pub fn new(system: impl Into<String>) -> Self {
+        WaypointQuery {
+            system: system.into(),
+            waypoint_type: None,
+            traits: Vec::new(),
+        }
+    }
+
+    pub fn waypoint_type(mut self, waypoint_type: WaypointType) -> Self {
+        self.waypoint_type = Some(waypoint_type);
+        self
+    }
+
+    pub fn with_trait(mut self, symbol: WaypointTraitSymbol) -> Self {
+        self.traits.push(symbol);
+        self
+    }
+
+    pub fn system(&self) -> &str {
+        &self.system
+    }
+
+    pub fn path(&self) -> String {
+        let mut params = Vec::new();
+        if let Some(waypoint_type) = &self.waypoint_type {
+            params.push(format!("type={}", waypoint_type));
+        }
+        for symbol in &self.traits {
+            params.push(format!("traits={}", symbol));
+        }
+        let mut path = format!("/systems/{}/waypoints", self.system);
+        if !params.is_empty() {
+            path.push('?');
+            path.push_str(&params.join("&"));
+        }
+        path
+    }
+}
+
+impl ApiClient {
+    pub fn waypoints(&self, query: &WaypointQuery) -> impl Stream<Item = Result<Waypoint>> + '_ {
+        self.paginate(query.path())
+    }
+
+    pub async fn find_waypoints(&self, query: &WaypointQuery) -> Result<Vec<Waypoint>> {
+        self.fetch_all(query.path()).await
+    }
+
+    pub async fn get_waypoint(&self, system: &str, waypoint_symbol: &str) -> Result<Waypoint> {
+        let body: GetWaypointResponse = self
+            .get(&format!(
+                "/systems/{}/waypoints/{}",
+                system, waypoint_symbol
+            ))
+            .await?;
+        Ok(body.data)
+    }
+
+    pub async fn get_system(&self, system: &str) -> Result<System> {
+        let body: GetSystemResponse = self.get(&format!("/systems/{}", system)).await?;
+        Ok(body.data)
+    }
+
+    pub async fn find_shipyards(&self, system: &str) -> Result<Vec<Waypoint>> {
+        self.find_waypoints(&WaypointQuery::new(system).with_trait(WaypointTraitSymbol::Shipyard))
+            .await
+    }
+
+    pub async fn waypoint_by_type(
+        &self,
+        system: &str,
+        waypoint_type: WaypointType,
+    ) -> Result<Vec<Waypoint>> {
+        self.find_waypoints(&WaypointQuery::new(system).waypoint_type(waypoint_type))
+            .await
+    }
+}
+
+/// The system part of a waypoint symbol, e.g. `X1-VM68` for `X1-VM68-A1`.
+pub fn system_symbol_of(waypoint_symbol: &str) -> &str {
+    match waypoint_symbol.rfind('-') {
+        Some(index) if waypoint_symbol[..index].contains('-') => &waypoint_symbol[..index],
+        _ => waypoint_symbol,
+    }
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use clap::Parser;
+
+use spacetraders::cli::Cli;
+use spacetraders::config::Config;
+use spacetraders::error::SpaceTradersError;
+
+#[tokio::test(start_paused = true)]
+async fn selling_a_good_not_in_the_hold_fails_without_a_sale() {
+    let server = common::start().await;
+    let cli = Cli::parse_from([
+        "SpaceTraders",
+        "ship",
+        "sell",
+        common::COMMAND_SHIP,
+        "iron_ore",
+    ]);
+
+    let result = cli.run(server.client(), &Config::default()).await;
+
+    match result {
+        Err(SpaceTradersError::Command { message }) => {
+            assert_eq!(message, "no IRON_ORE in hold")
+        }
+        other => panic!("expected a command error, got {:?}", other),
+    }
+    assert!(!server
+        .requests()
+        .iter()
+        .any(|request| request.ends_with("/sell")));
+}
@@ -0,0 +1,71 @@
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use chrono::Utc;
+
+use spacetraders::client::ApiClient;
+use spacetraders::clock::TokioClock;
+use spacetraders::contract_evaluator::ContractEvaluator;
+use spacetraders::fleet::FleetContext;
+use spacetraders::market_store::MarketStore;
+use spacetraders::mining::MiningConfig;
+use spacetraders::mock_server::{MockConfig, MockServer};
+use spacetraders::models::Ship;
+use spacetraders::navigation::Navigator;
+use spacetraders::survey_cache::SurveyCache;
+
+pub const COMMAND_SHIP: &str = "MOCK-AGENT-1";
+
+/// A mock server on the fixed world, running on the test's paused clock so
+/// cooldowns and flights pass as soon as everything is waiting on them.
+pub async fn start() -> MockServer {
+    MockServer::start(MockConfig {
+        clock: Arc::new(TokioClock::starting_at(Utc::now())),
+        ..MockConfig::default()
+    })
+    .await
+    .unwrap()
+}
+
+pub fn system(server: &MockServer) -> String {
+    server.state().world().system.clone()
+}
+
+pub fn waypoint(server: &MockServer, suffix: &str) -> String {
+    format!("{}-{}", system(server), suffix)
+}
+
+/// What the fleet controller would hand a ship's routine.
+pub async fn fleet_context(server: &MockServer) -> FleetContext {
+    client_context(server.client(), &system(server)).await
+}
+
+/// A fleet context working `system` through `client`, which need not talk
+/// to a mock server.
+pub async fn client_context(client: ApiClient, system: &str) -> FleetContext {
+    let planner = client.route_planner(system).await.unwrap();
+    FleetContext {
+        navigator: Navigator::new(client, Arc::new(planner)),
+        market_store: MarketStore::in_memory().shared(),
+        home_system: system.to_string(),
+        mining: MiningConfig::default(),
+        surveys: SurveyCache::new().shared(),
+        evaluator: ContractEvaluator::default(),
+        credit_reserve: 0,
+    }
+}
+
+pub async fn command_ship(server: &MockServer) -> Ship {
+    server.client().get_ship(COMMAND_SHIP).await.unwrap()
+}
+
+/// Records every market's prices, as a scouting probe would have.
+pub fn scout_markets(server: &MockServer, context: &FleetContext) {
+    let state = server.state();
+    let now = context.client().clock().utc_now();
+    let mut store = context.market_store.lock().unwrap();
+    for market in state.world().markets.values() {
+        store.record_market_at(market, now).unwrap();
+    }
+}
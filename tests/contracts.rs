@@ -0,0 +1,46 @@
+mod common;
+
+use std::time::Duration;
+
+use serde_json::json;
+
+use spacetraders::contracts;
+use spacetraders::mock_server::{InjectedError, MockError};
+
+#[tokio::test(start_paused = true)]
+async fn contract_runner_fulfils_the_offered_contract() {
+    let server = common::start().await;
+    let context = common::fleet_context(&server).await;
+    let ship = common::command_ship(&server).await;
+
+    let run = contracts::run(context, ship);
+    let result = tokio::time::timeout(Duration::from_secs(12 * 3600), run).await;
+
+    assert!(!matches!(result, Ok(Err(_))), "runner failed: {:?}", result);
+    let state = server.state();
+    let contract = state
+        .contracts()
+        .iter()
+        .find(|contract| contract.id == "mock-contract-1")
+        .unwrap();
+    assert!(contract.accepted);
+    assert!(contract.fulfilled);
+}
+
+#[tokio::test(start_paused = true)]
+async fn cooldown_rejections_do_not_count_as_extractions() {
+    let server = common::start().await;
+    let context = common::fleet_context(&server).await;
+    let ship = common::command_ship(&server).await;
+    let extract = format!("/my/ships/{}/extract", common::COMMAND_SHIP);
+    let cooldown = MockError::api(409, 4000, "Ship is on cooldown")
+        .with_data(json!({ "cooldown": { "remainingSeconds": 1 } }));
+    server.inject_error(InjectedError::new(extract, cooldown).times(100));
+
+    let run = contracts::run(context, ship);
+    let result = tokio::time::timeout(Duration::from_secs(12 * 3600), run).await;
+
+    assert!(!matches!(result, Ok(Err(_))), "runner failed: {:?}", result);
+    let state = server.state();
+    assert!(state.contracts()[0].fulfilled);
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use std::{fs, path::PathBuf};
+
+use spacetraders::client::ApiClient;
+use spacetraders::fixtures::{Fixtures, REDACTED};
+use spacetraders::mining::{Miner, MiningState};
+use spacetraders::models::{AvailableShips, Cargo, Contract, ExtractResult};
+
+/// A short session recorded from the mock server: contracts, the
+/// headquarters shipyard and one extraction at the engineered asteroid.
+const SESSION: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/mining-session");
+
+/// A miner's first hold recorded from the mock server: it resumes at
+/// headquarters, flies to the asteroid, extracts until full and sells at
+/// headquarters.
+const MINER_SESSION: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/miner-session");
+
+const SYSTEM: &str = "X1-MOCK";
+
+/// What a session saw, for comparing a recording with its replay.
+#[derive(Debug)]
+struct Session {
+    contracts: Vec<Contract>,
+    shipyard: AvailableShips,
+    extraction: ExtractResult,
+    cargo: Cargo,
+}
+
+/// Flies the command ship to the asteroid and mines once, looking at the
+/// contracts and the shipyard on the way out.
+async fn play(client: &ApiClient) -> Session {
+    let ship = common::COMMAND_SHIP;
+    let contracts = client.get_contracts().await.unwrap();
+    let shipyard = client
+        .find_ships_at_shipyard(SYSTEM, "X1-MOCK-A1")
+        .await
+        .unwrap();
+    client.send_ship_to_orbit(ship).await.unwrap();
+    let flight = client
+        .navigate_to_waypoint(ship, "X1-MOCK-B1")
+        .await
+        .unwrap();
+    client
+        .clock()
+        .sleep_until_utc(flight.nav.route.arrival)
+        .await;
+    let extraction = client.extract_ores(ship).await.unwrap();
+    let cargo = client.get_ship_cargo(ship).await.unwrap();
+    Session {
+        contracts,
+        shipyard,
+        extraction,
+        cargo,
+    }
+}
+
+fn remaining(client: &ApiClient) -> usize {
+    match client.fixtures() {
+        Some(Fixtures::Replay(replay)) => replay.remaining(),
+        _ => panic!("not a replaying client"),
+    }
+}
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("spacetraders-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[tokio::test]
+async fn checked_in_session_replays() {
+    let client = ApiClient::replay(SESSION).unwrap();
+
+    let session = play(&client).await;
+
+    assert_eq!(remaining(&client), 0);
+    assert_eq!(session.contracts[0].id, "mock-contract-1");
+    assert!(!session.contracts[0].terms.deliveries.is_empty());
+    assert!(!session.shipyard.ship_types.unwrap_or_default().is_empty());
+    let extracted = &session.extraction.extraction.extraction_yield;
+    assert!(extracted.units > 0);
+    assert_eq!(session.cargo.units, extracted.units);
+    assert!(session
+        .cargo
+        .inventory
+        .iter()
+        .any(|cargo| cargo.symbol == extracted.symbol));
+}
+
+#[tokio::test]
+async fn miner_replays_its_first_hold() {
+    let client = ApiClient::replay(MINER_SESSION).unwrap();
+    let context = common::client_context(client.clone(), SYSTEM).await;
+    let ship = client.get_ship(common::COMMAND_SHIP).await.unwrap();
+    let mut miner = Miner::new(context, ship, "X1-MOCK-B1".to_string());
+
+    miner.start().await.unwrap();
+    assert_eq!(*miner.state(), MiningState::Returning);
+    miner.advance().await.unwrap();
+    assert_eq!(*miner.state(), MiningState::Extracting);
+    assert_eq!(miner.ship().nav.waypoint_symbol, "X1-MOCK-B1");
+
+    let mut extractions = 0;
+    while *miner.state() == MiningState::Extracting {
+        miner.advance().await.unwrap();
+        extractions += 1;
+    }
+    assert_eq!(extractions, 4);
+    assert_eq!(
+        *miner.state(),
+        MiningState::Selling {
+            market: "X1-MOCK-A1".to_string()
+        }
+    );
+    let cargo = &miner.ship().cargo;
+    assert_eq!(cargo.units, cargo.capacity);
+
+    miner.advance().await.unwrap();
+    assert_eq!(*miner.state(), MiningState::Returning);
+    assert_eq!(miner.ship().nav.waypoint_symbol, "X1-MOCK-A1");
+    assert_eq!(miner.ship().cargo.units, 0);
+    assert_eq!(remaining(&client), 0);
+}
+
+#[tokio::test]
+async fn replay_fails_once_the_recording_runs_out() {
+    let client = ApiClient::replay(SESSION).unwrap();
+    client.get_contracts().await.unwrap();
+
+    assert!(client.get_contracts().await.is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn recorded_session_replays_the_same() {
+    let dir = scratch_dir("round-trip");
+    let server = common::start().await;
+    let token = server.state().token().to_string();
+
+    let recording = server.client().recording(&dir).unwrap();
+    let recorded = play(&recording).await;
+    let replay = ApiClient::replay(&dir).unwrap();
+    let replayed = play(&replay).await;
+
+    assert_eq!(remaining(&replay), 0);
+    assert_eq!(format!("{:?}", recorded), format!("{:?}", replayed));
+    for entry in fs::read_dir(&dir).unwrap() {
+        let text = fs::read_to_string(entry.unwrap().path()).unwrap();
+        assert!(!text.contains(&token));
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn recordings_redact_the_token() {
+    let dir = scratch_dir("redaction");
+    let server = common::start().await;
+
+    let client = ApiClient::new(server.url()).recording(&dir).unwrap();
+    let registered = client
+        .register_new_agent("REDACT-TEST", "COSMIC")
+        .await
+        .unwrap();
+    let token = registered["data"]["token"].as_str().unwrap().to_string();
+    let client = ApiClient::new(server.url())
+        .with_token(&token)
+        .recording(&dir)
+        .unwrap();
+    client.get_agent_data().await.unwrap();
+
+    let files: Vec<String> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+        .collect();
+    assert_eq!(files.len(), 2);
+    for text in &files {
+        assert!(!text.contains(&token));
+    }
+    assert!(files
+        .iter()
+        .any(|text| text.contains(&format!("Bearer {}", REDACTED))));
+    assert!(files
+        .iter()
+        .any(|text| text.contains(&format!("\"token\": \"{}\"", REDACTED))));
+    fs::remove_dir_all(&dir).unwrap();
+}
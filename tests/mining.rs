@@ -0,0 +1,45 @@
+mod common;
+
+use std::time::Duration;
+
+use spacetraders::mining::Miner;
+
+/// Game time a miner gets: enough for a few holds.
+const SHIFT: Duration = Duration::from_secs(4 * 3600);
+
+#[tokio::test(start_paused = true)]
+async fn miner_sells_what_it_extracts() {
+    let server = common::start().await;
+    let context = common::fleet_context(&server).await;
+    let ship = common::command_ship(&server).await;
+    let starting_credits = server.state().agent().credits;
+
+    let mut miner = Miner::new(context, ship, common::waypoint(&server, "B1"));
+    let result = tokio::time::timeout(SHIFT, miner.run()).await;
+
+    assert!(result.is_err(), "miner stopped: {:?}", result);
+    let requests = server.requests();
+    assert!(requests.iter().any(|request| request.ends_with("/extract")));
+    assert!(requests.iter().any(|request| request.ends_with("/sell")));
+    assert!(server.state().agent().credits > starting_credits);
+}
+
+#[tokio::test(start_paused = true)]
+async fn miner_delivers_contract_goods() {
+    let server = common::start().await;
+    let context = common::fleet_context(&server).await;
+    let ship = common::command_ship(&server).await;
+    server
+        .client()
+        .accept_contract("mock-contract-1")
+        .await
+        .unwrap();
+
+    let mut miner = Miner::new(context, ship, common::waypoint(&server, "B1"));
+    let result = tokio::time::timeout(SHIFT, miner.run()).await;
+
+    assert!(result.is_err(), "miner stopped: {:?}", result);
+    let state = server.state();
+    let contract = &state.contracts()[0];
+    assert!(contract.terms.deliveries[0].units_fulfilled > 0);
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use spacetraders::models::ShipNavStatus;
+
+#[tokio::test(start_paused = true)]
+async fn go_to_flies_to_a_distant_waypoint() {
+    let server = common::start().await;
+    let context = common::fleet_context(&server).await;
+    let mut ship = common::command_ship(&server).await;
+    let destination = common::waypoint(&server, "E1");
+
+    context
+        .navigator
+        .go_to(&mut ship, &destination)
+        .await
+        .unwrap();
+
+    assert_eq!(ship.nav.waypoint_symbol, destination);
+    let ship = common::command_ship(&server).await;
+    assert_eq!(ship.nav.waypoint_symbol, destination);
+    assert_ne!(ship.nav.status, ShipNavStatus::InTransit);
+}
+
+#[tokio::test(start_paused = true)]
+async fn go_to_the_current_waypoint_stays_put() {
+    let server = common::start().await;
+    let context = common::fleet_context(&server).await;
+    let mut ship = common::command_ship(&server).await;
+    let here = ship.nav.waypoint_symbol.clone();
+
+    context.navigator.go_to(&mut ship, &here).await.unwrap();
+
+    assert_eq!(ship.nav.waypoint_symbol, here);
+    assert!(!server
+        .requests()
+        .iter()
+        .any(|request| request.ends_with("/navigate")));
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use spacetraders::error::SpaceTradersError;
+use spacetraders::mock_server::{InjectedError, MockError};
+
+fn count(requests: &[String], wanted: &str) -> usize {
+    requests
+        .iter()
+        .filter(|request| request.as_str() == wanted)
+        .count()
+}
+
+#[tokio::test(start_paused = true)]
+async fn rate_limited_requests_are_retried() {
+    let server = common::start().await;
+    server.inject_error(InjectedError::new("/my/agent", MockError::rate_limited(2)).times(2));
+
+    let agent = server.client().get_agent_data().await.unwrap();
+
+    assert_eq!(agent.symbol, "MOCK-AGENT");
+    assert_eq!(count(&server.requests(), "GET /my/agent"), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn rate_limited_actions_are_retried() {
+    let server = common::start().await;
+    let orbit = format!("/my/ships/{}/orbit", common::COMMAND_SHIP);
+    server.inject_error(InjectedError::new(&orbit, MockError::rate_limited(1)));
+
+    server
+        .client()
+        .send_ship_to_orbit(common::COMMAND_SHIP)
+        .await
+        .unwrap();
+
+    assert_eq!(count(&server.requests(), &format!("POST {}", orbit)), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn retries_give_up_after_max_attempts() {
+    let server = common::start().await;
+    server.inject_error(InjectedError::new("/my/agent", MockError::rate_limited(1)).times(10));
+
+    let result = server.client().get_agent_data().await;
+
+    assert!(matches!(result, Err(SpaceTradersError::RateLimited { .. })));
+    let attempts = server.client().retry_policy().max_attempts as usize;
+    assert_eq!(count(&server.requests(), "GET /my/agent"), attempts);
+}
+
+#[tokio::test(start_paused = true)]
+async fn server_errors_on_actions_are_not_retried() {
+    let server = common::start().await;
+    let orbit = format!("/my/ships/{}/orbit", common::COMMAND_SHIP);
+    server.inject_error(InjectedError::new(
+        &orbit,
+        MockError::api(500, 0, "server melted"),
+    ));
+
+    let result = server
+        .client()
+        .send_ship_to_orbit(common::COMMAND_SHIP)
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(SpaceTradersError::Api { status: 500, .. })
+    ));
+    assert_eq!(count(&server.requests(), &format!("POST {}", orbit)), 1);
+}
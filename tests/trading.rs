@@ -0,0 +1,48 @@
+mod common;
+
+use std::time::Duration;
+
+use spacetraders::models::{Cargo, CargoObject, TradeSymbol};
+use spacetraders::trading;
+
+#[tokio::test(start_paused = true)]
+async fn trader_clears_a_hold_full_of_other_goods() {
+    let server = common::start().await;
+    let context = common::fleet_context(&server).await;
+    common::scout_markets(&server, &context);
+    {
+        let mut state = server.state();
+        let ship = state.ship(common::COMMAND_SHIP).unwrap();
+        let capacity = ship.cargo.capacity;
+        ship.cargo = Cargo {
+            capacity,
+            units: capacity,
+            inventory: vec![CargoObject {
+                description: String::new(),
+                name: "Quantum Drives".to_string(),
+                symbol: TradeSymbol::QuantumDrives,
+                units: capacity,
+            }],
+        };
+    }
+    let ship = common::command_ship(&server).await;
+
+    let run = trading::run(context, ship);
+    let result = tokio::time::timeout(Duration::from_secs(2 * 3600), run).await;
+
+    assert!(result.is_err(), "trader stopped: {:?}", result);
+    let requests = server.requests();
+    assert!(requests
+        .iter()
+        .any(|request| request.ends_with("/jettison")));
+    assert!(requests
+        .iter()
+        .any(|request| request.ends_with("/purchase")));
+    assert!(requests.iter().any(|request| request.ends_with("/sell")));
+    let ship = common::command_ship(&server).await;
+    assert!(!ship
+        .cargo
+        .inventory
+        .iter()
+        .any(|cargo| cargo.symbol == TradeSymbol::QuantumDrives));
+}